@@ -32,11 +32,73 @@ use std::thread;
 use std::sync::mpsc;
 use std::ptr::NonNull;
 use std::time;
+use std::collections::VecDeque;
 use rand::Rng;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 
 const SO_SUFFIX: &str = ".cuckooplugin";
 
+/// Capacity of each `subscribe_solutions()` channel. Bounded so a stalled
+/// consumer can't let solutions pile up unboundedly in memory; `broadcast_solution`
+/// uses `try_send` so a full channel just drops the newest solution for that
+/// one subscriber instead of blocking the solver thread that found it.
+const SOLUTION_CHANNEL_CAPACITY: usize = 64;
+
+/// How long a device's throughput samples are kept around before being
+/// pruned, i.e. the longest window `get_aggregate_stats` can average over.
+const SAMPLE_RETENTION_SECS: u64 = 15 * 60;
+
+/// How long `notify()` can go without refreshing the header before a
+/// solver pauses itself and marks `Idle` rather than keep mining a stale
+/// job indefinitely.
+const DEFAULT_JOB_STALENESS_TIMEOUT_SECS: u64 = 120;
+
+/// Default inter-iteration "tranquility" delay: no throttling.
+const DEFAULT_TRANQUILITY_MS: u32 = 0;
+
+/// Lower bound on the solver loop's wait between iterations, regardless of
+/// the configured tranquility value — keeps the loop responsive to control
+/// messages even when tranquility is `0`.
+const MIN_LOOP_WAIT: time::Duration = time::Duration::from_micros(100);
+
+/// One `iter_count` observation, timestamped so the aggregator can compute
+/// a rate without the consumer polling at a fixed cadence.
+#[derive(Debug, Clone, Copy)]
+struct StatsSample {
+	at: time::Instant,
+	iterations: u64,
+	edge_bits: u8,
+}
+
+/// Graphs/edges-per-second averaged over three rolling windows, the same
+/// 1s/1m/15m convention `uptime`'s load average uses.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RateWindow {
+	pub rate_1s: f64,
+	pub rate_1m: f64,
+	pub rate_15m: f64,
+}
+
+/// One device's slice of `AggregateStats`.
+#[derive(Debug, Clone)]
+pub struct DeviceAggregateStats {
+	pub plugin_name: String,
+	pub graph_rate: RateWindow,
+	pub edge_rate: RateWindow,
+}
+
+/// Throughput and solution counters aggregated across every running solver,
+/// returned by `PurpleMiner::get_aggregate_stats()`.
+#[derive(Debug, Clone)]
+pub struct AggregateStats {
+	pub per_device: Vec<DeviceAggregateStats>,
+	pub total_graph_rate: RateWindow,
+	pub total_edge_rate: RateWindow,
+	pub solutions_found: u64,
+	pub solutions_passed_difficulty: u64,
+	pub solutions_rejected: u64,
+}
+
 /// Miner control Messages
 #[derive(Debug)]
 enum ControlMessage {
@@ -48,9 +110,70 @@ enum ControlMessage {
 
 	/// Resume
     Resume,
-	
+
     /// Solver reporting stopped
 	SolverStopped(usize),
+
+	/// Solver reporting it has observed and applied a `Pause`
+	PauseAck(usize),
+
+	/// Solver reporting it has observed and applied a `Resume`
+	ResumeAck(usize),
+
+	/// Sets the inter-iteration "tranquility" delay, in milliseconds, that
+	/// every solver thread waits between solve attempts — a runtime-
+	/// adjustable duty-cycle throttle for capping CPU/GPU utilization.
+	SetTranquility(u32),
+}
+
+/// How solver instances split the nonce space for a given job so that
+/// running `n` of them covers `n` times the search space instead of
+/// redundantly overlapping. Configured per-plugin via
+/// `PluginConfig::nonce_partition_strategy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoncePartitionStrategy {
+	/// Instance `i` of `n` takes `job_id + i + k*n` on loop iteration `k` —
+	/// a fixed stride through the nonce space that the whole group sweeps
+	/// round-robin.
+	Striped,
+
+	/// Instance `i` of `n` is assigned a disjoint contiguous sub-range of
+	/// `u64`, offset by a value derived from the job id, and walks forward
+	/// within it one nonce per iteration.
+	Ranged,
+
+	/// The original, uncoordinated behavior: an independent random nonce
+	/// every iteration. Kept as an option since it needs no coordination
+	/// state, at the cost of solvers occasionally exploring the same nonce.
+	Random,
+}
+
+impl Default for NoncePartitionStrategy {
+	fn default() -> Self {
+		NoncePartitionStrategy::Striped
+	}
+}
+
+/// A solver instance's current lifecycle state, exposed via
+/// `PurpleMiner::get_worker_states()` — lets a caller list every worker and
+/// tell whether it's actively searching, idle, paused, dead, or errored
+/// instead of only firing control messages at it with no feedback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerState {
+	/// Thread is up but hasn't begun (or isn't between) a solve attempt.
+	Idle,
+
+	/// Inside a solve attempt.
+	Active,
+
+	/// Told to pause via `pause_solvers` and waiting to be resumed.
+	Paused,
+
+	/// The last solve attempt set `has_errored`; the thread is exiting.
+	Errored,
+
+	/// The thread has fully exited.
+	Stopped,
 }
 
 pub struct PurpleMiner {
@@ -68,32 +191,76 @@ pub struct PurpleMiner {
 
 	/// Solver has stopped and cleanly shutdown
 	solver_stopped_rxs: Vec<Receiver<ControlMessage>>,
+
+	/// Solver has acknowledged a Pause/Resume transition
+	ack_rxs: Vec<Receiver<ControlMessage>>,
+
+	/// Fan-out list of `subscribe_solutions()` channels; `solver_thread`
+	/// pushes a newly found, difficulty-filtered solution to each of these
+	/// directly, instead of consumers polling `shared_data` under its write
+	/// lock.
+	solution_txs: Arc<RwLock<Vec<mpsc::SyncSender<SolverSolutions>>>>,
+
+	/// The channel `get_solutions()` itself drains, registered as just
+	/// another subscriber so the old and new APIs share one push path.
+	default_solution_rx: Mutex<Receiver<SolverSolutions>>,
+}
+
+/// Pushes `solution` to every subscriber registered via
+/// `subscribe_solutions()`, dropping any whose receiver has disconnected.
+/// Uses `try_send` so one stalled consumer can't block the solver thread
+/// that found the solution - a full channel just drops the newest
+/// solution for that one subscriber.
+fn broadcast_solution(
+	solution_txs: &Arc<RwLock<Vec<mpsc::SyncSender<SolverSolutions>>>>,
+	solution: SolverSolutions,
+) {
+	let mut txs = solution_txs.write();
+	txs.retain(|tx| match tx.try_send(solution.clone()) {
+		Ok(()) | Err(mpsc::TrySendError::Full(_)) => true,
+		Err(mpsc::TrySendError::Disconnected(_)) => false,
+	});
 }
 
 impl PurpleMiner {
     pub fn new(configs: Vec<PluginConfig>) -> PurpleMiner {
 		let len = configs.len();
-		PurpleMiner {
+		let shared_data = JobSharedData::new(len);
+		let (default_solution_tx, default_solution_rx) = mpsc::sync_channel(SOLUTION_CHANNEL_CAPACITY);
+		let miner = PurpleMiner {
 			configs: configs,
-			shared_data: Arc::new(RwLock::new(JobSharedData::new(len))),
+			shared_data: Arc::new(RwLock::new(shared_data)),
 			control_txs: vec![],
 			solver_loop_txs: vec![],
 			solver_stopped_rxs: vec![],
+			ack_rxs: vec![],
+			solution_txs: Arc::new(RwLock::new(vec![default_solution_tx])),
+			default_solution_rx: Mutex::new(default_solution_rx),
+		};
+		{
+			let mut sd = miner.shared_data.write();
+			sd.tranquility = DEFAULT_TRANQUILITY_MS;
+			sd.last_job_update = time::Instant::now();
 		}
+		miner
 	}
 
 	/// Solver's instance of a thread
 	fn solver_thread(
 		mut solver: SolverInstance,
 		instance: usize,
+		n_solvers: usize,
 		shared_data: Arc<RwLock<JobSharedData>>,
 		control_rx: mpsc::Receiver<ControlMessage>,
 		solver_loop_rx: mpsc::Receiver<ControlMessage>,
 		solver_stopped_tx: mpsc::Sender<ControlMessage>,
+		ack_tx: mpsc::Sender<ControlMessage>,
+		solution_txs: Arc<RwLock<Vec<mpsc::SyncSender<SolverSolutions>>>>,
 	) {
 		{
 			let mut s = shared_data.write();
 			s.stats[instance].set_plugin_name(&solver.config.name);
+			s.worker_states[instance] = WorkerState::Idle;
 		}
 		// "Detach" a stop function from the solver, to let us keep a control thread going
 		let ctx = solver.lib.create_solver_ctx(&mut solver.config.params);
@@ -121,31 +288,104 @@ impl PurpleMiner {
 
 		let mut iter_count = 0;
 		let mut paused = true;
+
+		// Nonce-partitioning state: `k` is this instance's per-job
+		// iteration counter, reset whenever the job height changes (the
+		// loop below already re-reads `height` every iteration and checks
+		// `still_valid` for the same reason).
+		let mut nonce_job_height: u64 = 0;
+		let mut k: u64 = 0;
+
 		loop {
-			if let Some(message) = solver_loop_rx.try_iter().next() {
-				// debug!("solver_thread - solver_loop_rx got msg: {:?}", message);
-				match message {
-					ControlMessage::Stop => break,
-					ControlMessage::Pause => paused = true,
-					ControlMessage::Resume => paused = false,
-					_ => {}
+			// A dedicated timer wheel is overkill for a handful of solver
+			// threads; `recv_timeout` gives us a single blocking wait that
+			// doubles as both the control-message drain and the
+			// tranquility delay below, waking early whenever a message
+			// arrives instead of polling.
+			let wait = {
+				let tranquility_ms = shared_data.read().tranquility;
+				time::Duration::from_millis(tranquility_ms as u64).max(MIN_LOOP_WAIT)
+			};
+			match solver_loop_rx.recv_timeout(wait) {
+				Ok(message) => {
+					// debug!("solver_thread - solver_loop_rx got msg: {:?}", message);
+					match message {
+						ControlMessage::Stop => break,
+						ControlMessage::Pause => {
+							paused = true;
+							shared_data.write().worker_states[instance] = WorkerState::Paused;
+							let _ = ack_tx.send(ControlMessage::PauseAck(instance));
+						}
+						ControlMessage::Resume => {
+							paused = false;
+							let _ = ack_tx.send(ControlMessage::ResumeAck(instance));
+						}
+						ControlMessage::SetTranquility(ms) => {
+							shared_data.write().tranquility = ms;
+						}
+						_ => {}
+					}
 				}
+				Err(mpsc::RecvTimeoutError::Timeout) => {}
+				Err(mpsc::RecvTimeoutError::Disconnected) => break,
+			}
+
+			// Per-job staleness: if `notify()` hasn't refreshed the header
+			// within the timeout, pause ourselves and mark `Idle` rather
+			// than keep mining it indefinitely.
+			let stale = {
+				let s = shared_data.read();
+				s.last_job_update.elapsed()
+					> time::Duration::from_secs(
+						solver.config.job_staleness_timeout_secs.max(1),
+					)
+			};
+			if stale && !paused {
+				paused = true;
+				shared_data.write().worker_states[instance] = WorkerState::Idle;
 			}
+
 			if paused {
-				thread::sleep(time::Duration::from_micros(100));
+				// entering the paused sleep
 				continue;
 			}
 			{
 				let mut s = shared_data.write();
 				s.stats[instance].set_plugin_name(&solver.config.name);
+				// beginning a solve
+				s.worker_states[instance] = WorkerState::Active;
 			}
 			let header = { shared_data.read().header.clone() };
 			let height = { shared_data.read().height.clone() };
 			let job_id = { shared_data.read().job_id.clone() };
 			let target_difficulty = { shared_data.read().difficulty.clone() };
-			
-            // Gen random nonce
-            let nonce: u64 = rand::OsRng::new().unwrap().gen();
+
+			if height != nonce_job_height {
+				nonce_job_height = height;
+				k = 0;
+			}
+
+			// Deterministically partition the nonce space across instances
+			// so `n_solvers` of them cover `n_solvers` times the search
+			// space instead of redundantly overlapping (see
+			// `NoncePartitionStrategy`).
+			let nonce: u64 = match solver.config.nonce_partition_strategy {
+				NoncePartitionStrategy::Striped => {
+					let n = n_solvers.max(1) as u64;
+					(job_id as u64)
+						.wrapping_add(instance as u64)
+						.wrapping_add(k.wrapping_mul(n))
+				}
+				NoncePartitionStrategy::Ranged => {
+					let n = n_solvers.max(1) as u64;
+					let range_size = u64::max_value() / n;
+					let range_start = range_size.wrapping_mul(instance as u64);
+					let job_offset = (job_id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) % range_size;
+					range_start.wrapping_add(job_offset).wrapping_add(k)
+				}
+				NoncePartitionStrategy::Random => rand::OsRng::new().unwrap().gen(),
+			};
+			k += 1;
 
 			solver.lib.run_solver(
 				ctx,
@@ -157,11 +397,35 @@ impl PurpleMiner {
 			);
 			iter_count += 1;
 			let still_valid = { height == shared_data.read().height };
+			let mut found_solution = None;
 			if still_valid {
 				let mut s = shared_data.write();
 				s.stats[instance] = solver.stats.clone();
 				s.stats[instance].iterations = iter_count;
+
+				// Timestamp this iteration so the aggregator can compute a
+				// rate without the consumer polling at a fixed cadence, then
+				// drop anything older than the longest window it supports.
+				let now = time::Instant::now();
+				s.samples[instance].push_back(StatsSample {
+					at: now,
+					iterations: iter_count,
+					edge_bits: solver.solutions.edge_bits as u8,
+				});
+				let cutoff = now
+					.checked_sub(time::Duration::from_secs(SAMPLE_RETENTION_SECS))
+					.unwrap_or(now);
+				while let Some(oldest) = s.samples[instance].front() {
+					if oldest.at < cutoff {
+						s.samples[instance].pop_front();
+					} else {
+						break;
+					}
+				}
+
 				if solver.solutions.num_sols > 0 {
+					s.solutions_found[instance] += solver.solutions.num_sols as u64;
+
 					// Filter solutions that don't meet difficulty check
 					let mut filtered_sols:Vec<Solution> = vec![];
 					for i in 0..solver.solutions.num_sols {
@@ -187,10 +451,12 @@ impl PurpleMiner {
 					for i in 0..solver.solutions.num_sols as usize {
 						solver.solutions.sols[i] = filtered_sols[i];
 					}
-					s.solutions.push(solver.solutions.clone());
+					s.solutions_passed_difficulty[instance] += solver.solutions.num_sols as u64;
+					found_solution = Some(solver.solutions.clone());
 				}
 				if s.stats[instance].has_errored {
 					s.stats[instance].set_plugin_name(&solver.config.name);
+					s.worker_states[instance] = WorkerState::Errored;
 					// error!(
 					// 	LOGGER,
 					// 	"Plugin {} has errored, device: {}. Reason: {}",
@@ -201,10 +467,22 @@ impl PurpleMiner {
 					break;
 				}
 			}
+			if let Some(solution) = found_solution {
+				// Pushed directly to subscribers outside the `shared_data` lock
+				// above, so a slow consumer never blocks the stats writer.
+				broadcast_solution(&solution_txs, solution);
+			}
 			solver.solutions = SolverSolutions::default();
-			thread::sleep(time::Duration::from_micros(100));
+			// No trailing sleep here: the next loop iteration's
+			// `recv_timeout` above already provides the tranquility delay.
 		}
 
+		{
+			let mut s = shared_data.write();
+			if s.worker_states[instance] != WorkerState::Errored {
+				s.worker_states[instance] = WorkerState::Stopped;
+			}
+		}
 		let _ = stop_handle.join();
 		solver.lib.destroy_solver_ctx(ctx);
 		solver.unload();
@@ -217,18 +495,24 @@ impl PurpleMiner {
 		for c in self.configs.clone() {
 			solvers.push(SolverInstance::new(c)?);
 		}
+		let n_solvers = solvers.len();
 		let mut i = 0;
 		for s in solvers {
 			let sd = self.shared_data.clone();
 			let (control_tx, control_rx) = mpsc::channel::<ControlMessage>();
 			let (solver_tx, solver_rx) = mpsc::channel::<ControlMessage>();
 			let (solver_stopped_tx, solver_stopped_rx) = mpsc::channel::<ControlMessage>();
+			let (ack_tx, ack_rx) = mpsc::channel::<ControlMessage>();
+			let solution_txs = self.solution_txs.clone();
 			self.control_txs.push(control_tx);
 			self.solver_loop_txs.push(solver_tx);
 			self.solver_stopped_rxs.push(solver_stopped_rx);
+			self.ack_rxs.push(ack_rx);
 			thread::spawn(move || {
-				let _ =
-					PurpleMiner::solver_thread(s, i, sd, control_rx, solver_rx, solver_stopped_tx);
+				let _ = PurpleMiner::solver_thread(
+					s, i, n_solvers, sd, control_rx, solver_rx, solver_stopped_tx, ack_tx,
+					solution_txs,
+				);
 			});
 			i += 1;
 		}
@@ -249,47 +533,63 @@ impl PurpleMiner {
 		&mut self,
 		job_id: u32,      // Job id
 		height: u64,      // Job height
-		header: &[u8],  
+		header: &[u8],
 		difficulty: u64,  /* The target difficulty, only sols greater than this difficulty will
 		                   * be returned. */
 	) -> Result<(), CuckooMinerError> {
-		let mut sd = self.shared_data.write();
-		let mut paused = false;
-		if height != sd.height {
-			// stop/pause any existing jobs if job is for a new
-			// height
-			self.pause_solvers();
-			paused = true;
+		// Stop/pause any existing jobs if job is for a new height, and block
+		// until every solver has acknowledged the pause before touching
+		// `shared_data` below — otherwise a solver could still be mid-solve
+		// on the old header when we resume it, having never observed the
+		// pause at all.
+		let paused = height != self.shared_data.read().height;
+		if paused {
+			self.pause_solvers(true);
+		}
+		{
+			let mut sd = self.shared_data.write();
+			sd.job_id = job_id;
+			sd.height = height;
+			sd.header = header.to_vec();
+			sd.difficulty = difficulty;
+			// Marks the header as fresh so the staleness check in
+			// `solver_thread` doesn't pause solvers that are, in fact,
+			// still being kept up to date.
+			sd.last_job_update = time::Instant::now();
 		}
-		sd.job_id = job_id;
-		sd.height = height;
-		sd.header = header.to_vec();
-		sd.difficulty = difficulty;
 		if paused {
-			self.resume_solvers();
+			self.resume_solvers(true);
 		}
 		Ok(())
 	}
 
-	/// Returns solutions if currently waiting.
+	/// Sets the inter-iteration "tranquility" delay every solver thread
+	/// waits between solve attempts, in milliseconds — `0` disables
+	/// throttling. Takes effect on each solver's next loop iteration.
+	pub fn set_tranquility(&self, tranquility_ms: u32) {
+		self.shared_data.write().tranquility = tranquility_ms;
+		for t in self.solver_loop_txs.iter() {
+			let _ = t.send(ControlMessage::SetTranquility(tranquility_ms));
+		}
+	}
 
+	/// Subscribes to newly found, difficulty-filtered solutions as they're
+	/// produced, instead of polling `get_solutions()` under `shared_data`'s
+	/// write lock. Each call registers a new fan-out channel, so multiple
+	/// independent consumers (e.g. a Stratum submitter and a local logger)
+	/// can each get every solution without stealing it from one another.
+	pub fn subscribe_solutions(&self) -> Receiver<SolverSolutions> {
+		let (tx, rx) = mpsc::sync_channel(SOLUTION_CHANNEL_CAPACITY);
+		self.solution_txs.write().push(tx);
+		rx
+	}
+
+	/// Returns a solution if one is currently waiting. Kept for existing
+	/// callers; internally this just drains the same fan-out channel
+	/// `subscribe_solutions()` consumers do, so it no longer takes
+	/// `shared_data`'s write lock at all.
 	pub fn get_solutions(&self) -> Option<SolverSolutions> {
-		// just to prevent endless needless locking of this
-		// when using fast test miners, in real cuckoo30 terms
-		// this shouldn't be an issue
-		// TODO: Make this less blocky
-		// let time_pre_lock=Instant::now();
-		{
-			let mut s = self.shared_data.write();
-			// let time_elapsed=Instant::now()-time_pre_lock;
-			// println!("Get_solution Time spent waiting for lock: {}",
-			// time_elapsed.as_secs()*1000 +(time_elapsed.subsec_nanos()/1_000_000)as u64);
-			if s.solutions.len() > 0 {
-				let sol = s.solutions.pop().unwrap();
-				return Some(sol);
-			}
-		}
-		None
+		self.default_solution_rx.lock().try_recv().ok()
 	}
 
 	/// get stats for all running solvers
@@ -298,6 +598,109 @@ impl PurpleMiner {
 		Ok(s.stats.clone())
 	}
 
+	/// The current inter-iteration tranquility delay (milliseconds), for
+	/// `get_stats()` consumers that want to display the active throttle
+	/// alongside per-solver stats.
+	pub fn get_tranquility(&self) -> u32 {
+		self.shared_data.read().tranquility
+	}
+
+	/// Returns each solver's current lifecycle state (see `WorkerState`),
+	/// e.g. to list which workers are active, idle, paused, or dead.
+	pub fn get_worker_states(&self) -> Vec<WorkerState> {
+		self.shared_data.read().worker_states.clone()
+	}
+
+	/// Returns per-device and summed 1s/1m/15m graph/edge throughput, plus
+	/// cumulative solution counters, computed from each device's ring
+	/// buffer of timestamped `iter_count` samples (see `StatsSample`).
+	pub fn get_aggregate_stats(&self) -> AggregateStats {
+		let s = self.shared_data.read();
+		let now = time::Instant::now();
+
+		let mut per_device = Vec::with_capacity(s.stats.len());
+		let mut total_graph_rate = RateWindow::default();
+		let mut total_edge_rate = RateWindow::default();
+
+		for i in 0..s.stats.len() {
+			let (graph_rate, edge_rate) = Self::rate_windows(&s.samples[i], now);
+			total_graph_rate.rate_1s += graph_rate.rate_1s;
+			total_graph_rate.rate_1m += graph_rate.rate_1m;
+			total_graph_rate.rate_15m += graph_rate.rate_15m;
+			total_edge_rate.rate_1s += edge_rate.rate_1s;
+			total_edge_rate.rate_1m += edge_rate.rate_1m;
+			total_edge_rate.rate_15m += edge_rate.rate_15m;
+
+			per_device.push(DeviceAggregateStats {
+				plugin_name: s.stats[i].get_plugin_name(),
+				graph_rate,
+				edge_rate,
+			});
+		}
+
+		AggregateStats {
+			per_device,
+			total_graph_rate,
+			total_edge_rate,
+			solutions_found: s.solutions_found.iter().sum(),
+			solutions_passed_difficulty: s.solutions_passed_difficulty.iter().sum(),
+			solutions_rejected: s.solutions_rejected,
+		}
+	}
+
+	/// Called by a pool/consumer (e.g. the Stratum client) when a submitted
+	/// solution is rejected, so `get_aggregate_stats()` can report it
+	/// without that consumer needing to know which solver instance found it.
+	pub fn record_solution_rejected(&self) {
+		self.shared_data.write().solutions_rejected += 1;
+	}
+
+	/// Averages `samples`'s graph/edge rate over the trailing 1s/1m/15m
+	/// windows ending at `now`.
+	fn rate_windows(samples: &VecDeque<StatsSample>, now: time::Instant) -> (RateWindow, RateWindow) {
+		let (g1, e1) = Self::window_rate(samples, time::Duration::from_secs(1), now);
+		let (g60, e60) = Self::window_rate(samples, time::Duration::from_secs(60), now);
+		let (g900, e900) = Self::window_rate(samples, time::Duration::from_secs(900), now);
+		(
+			RateWindow { rate_1s: g1, rate_1m: g60, rate_15m: g900 },
+			RateWindow { rate_1s: e1, rate_1m: e60, rate_15m: e900 },
+		)
+	}
+
+	/// Graph/edge rate between the oldest and newest sample still inside
+	/// `window`, or `(0.0, 0.0)` if fewer than two samples fall in it.
+	fn window_rate(
+		samples: &VecDeque<StatsSample>,
+		window: time::Duration,
+		now: time::Instant,
+	) -> (f64, f64) {
+		let cutoff = now.checked_sub(window).unwrap_or(now);
+		let mut earliest: Option<StatsSample> = None;
+		let mut latest: Option<StatsSample> = None;
+
+		for sample in samples.iter() {
+			if sample.at >= cutoff {
+				if earliest.is_none() {
+					earliest = Some(*sample);
+				}
+				latest = Some(*sample);
+			}
+		}
+
+		match (earliest, latest) {
+			(Some(e), Some(l)) if l.at > e.at && l.iterations >= e.iterations => {
+				let elapsed = l.at.duration_since(e.at).as_secs_f64();
+				if elapsed <= 0.0 {
+					return (0.0, 0.0);
+				}
+				let graph_rate = (l.iterations - e.iterations) as f64 / elapsed;
+				let edge_rate = graph_rate * 2f64.powi(l.edge_bits as i32);
+				(graph_rate, edge_rate)
+			}
+			_ => (0.0, 0.0),
+		}
+	}
+
 	/// #Description
 	///
 	/// Stops the current job, and signals for the loaded plugin to stop
@@ -317,8 +720,11 @@ impl PurpleMiner {
 		// debug!("Stop message sent");
 	}
 
-	/// Tells current solvers to stop and wait
-	pub fn pause_solvers(&self) {
+	/// Tells current solvers to pause. If `wait_for_ack` is set, blocks
+	/// until every solver has observed and applied the pause (see
+	/// `WorkerState::Paused`) before returning, closing the race where a
+	/// caller resumes solvers that never actually stopped searching.
+	pub fn pause_solvers(&self, wait_for_ack: bool) {
 		for t in self.control_txs.iter() {
 			let _ = t.send(ControlMessage::Pause);
 		}
@@ -326,10 +732,14 @@ impl PurpleMiner {
 			let _ = t.send(ControlMessage::Pause);
 		}
 		// debug!("Pause message sent");
+		if wait_for_ack {
+			self.wait_for_pause_acks();
+		}
 	}
 
-	/// Tells current solvers to stop and wait
-	pub fn resume_solvers(&self) {
+	/// Tells current solvers to resume. If `wait_for_ack` is set, blocks
+	/// until every solver has acknowledged the resume before returning.
+	pub fn resume_solvers(&self, wait_for_ack: bool) {
 		for t in self.control_txs.iter() {
 			let _ = t.send(ControlMessage::Resume);
 		}
@@ -337,6 +747,9 @@ impl PurpleMiner {
 			let _ = t.send(ControlMessage::Resume);
 		}
 		// debug!("Resume message sent");
+		if wait_for_ack {
+			self.wait_for_resume_acks();
+		}
 	}
 
 	/// block until solvers have all exited
@@ -353,6 +766,30 @@ impl PurpleMiner {
 			}
 		}
 	}
+
+	/// block until every solver has acknowledged a `Pause`
+	fn wait_for_pause_acks(&self) {
+		for r in self.ack_rxs.iter() {
+			while let Some(message) = r.iter().next() {
+				match message {
+					ControlMessage::PauseAck(_) => break,
+					_ => continue,
+				}
+			}
+		}
+	}
+
+	/// block until every solver has acknowledged a `Resume`
+	fn wait_for_resume_acks(&self) {
+		for r in self.ack_rxs.iter() {
+			while let Some(message) = r.iter().next() {
+				match message {
+					ControlMessage::ResumeAck(_) => break,
+					_ => continue,
+				}
+			}
+		}
+	}
 }
 
 fn load_plugin_lib(plugin: &str) -> Result<PluginLibrary, CuckooMinerError> {
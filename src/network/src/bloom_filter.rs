@@ -0,0 +1,97 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+/// A classic Bloom filter over 64-bit keys, used as the first stage of
+/// Graphene-style set reconciliation: the sender transmits a filter sized to
+/// a target false-positive rate, and the receiver runs its own mempool
+/// through it to obtain a candidate set before building an IBLT.
+#[derive(Debug, Clone)]
+pub struct BloomFilter {
+    bits: Vec<bool>,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    /// Builds a filter sized for `expected_items` entries at `false_positive_rate`.
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> BloomFilter {
+        let expected_items = expected_items.max(1) as f64;
+        let num_bits =
+            (-(expected_items * false_positive_rate.ln()) / (2.0_f64.ln().powi(2))).ceil();
+        let num_bits = (num_bits as usize).max(8);
+        let num_hashes = ((num_bits as f64 / expected_items) * 2.0_f64.ln()).round() as u32;
+        let num_hashes = num_hashes.max(1);
+
+        BloomFilter {
+            bits: vec![false; num_bits],
+            num_hashes,
+        }
+    }
+
+    /// Inserts a key into the filter.
+    pub fn insert(&mut self, key: u64) {
+        for idx in self.indices(key) {
+            self.bits[idx] = true;
+        }
+    }
+
+    /// Returns true if `key` may be a member (may false-positive, never
+    /// false-negative).
+    pub fn contains(&self, key: u64) -> bool {
+        self.indices(key).all(|idx| self.bits[idx])
+    }
+
+    /// Computes the `num_hashes` independent bit indices for `key` using the
+    /// standard double-hashing trick (`h1 + i*h2`).
+    fn indices(&self, key: u64) -> impl Iterator<Item = usize> + '_ {
+        let h1 = fnv1a(key, 0xcbf2_9ce4_8422_2325);
+        let h2 = fnv1a(key, 0x1000_0000_01b3_1b3b);
+        let len = self.bits.len() as u64;
+
+        (0..self.num_hashes).map(move |i| (h1.wrapping_add((i as u64).wrapping_mul(h2)) % len) as usize)
+    }
+}
+
+/// A tiny FNV-1a variant seeded by `seed`, used to derive independent hash
+/// functions for the Bloom filter and IBLT cell placement without pulling in
+/// an extra dependency.
+fn fnv1a(key: u64, seed: u64) -> u64 {
+    let mut hash = seed;
+    for byte in key.to_le_bytes().iter() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_never_false_negatives() {
+        let mut filter = BloomFilter::new(100, 0.01);
+
+        for key in 0..100u64 {
+            filter.insert(key);
+        }
+
+        for key in 0..100u64 {
+            assert!(filter.contains(key));
+        }
+    }
+}
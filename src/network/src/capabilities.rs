@@ -0,0 +1,94 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::ops::{BitAnd, BitOr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// A bitflags-style set of roles/features a peer advertises, encoded as a
+/// single byte. Lets the dispatch layer route packets only to peers that
+/// negotiated support for them, instead of sending packets a peer cannot
+/// handle and finding out by trial and error. The `Connect` packet does not
+/// carry this byte.
+pub struct Capabilities(u8);
+
+impl Capabilities {
+    pub const NONE: Capabilities = Capabilities(0);
+    pub const FULL_NODE: Capabilities = Capabilities(0b0000_0001);
+    pub const BLOCK_PROPAGATION: Capabilities = Capabilities(0b0000_0010);
+    pub const POOL_VALIDATOR: Capabilities = Capabilities(0b0000_0100);
+    pub const LIGHT_CLIENT: Capabilities = Capabilities(0b0000_1000);
+    pub const ARCHIVAL: Capabilities = Capabilities(0b0001_0000);
+
+    /// Decodes a set of capabilities from its single-byte encoding.
+    pub fn from_byte(byte: u8) -> Capabilities {
+        Capabilities(byte)
+    }
+
+    /// Encodes this capability set as a single byte.
+    pub fn to_byte(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns true if `self` advertises every capability set in `other`.
+    pub fn contains(&self, other: Capabilities) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    /// Returns the capabilities negotiated in common between two peers.
+    pub fn intersection(&self, other: Capabilities) -> Capabilities {
+        Capabilities(self.0 & other.0)
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Capabilities;
+
+    fn bitor(self, rhs: Capabilities) -> Capabilities {
+        Capabilities(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for Capabilities {
+    type Output = Capabilities;
+
+    fn bitand(self, rhs: Capabilities) -> Capabilities {
+        Capabilities(self.0 & rhs.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_round_trips_through_a_byte() {
+        let caps = Capabilities::FULL_NODE | Capabilities::BLOCK_PROPAGATION;
+        assert_eq!(Capabilities::from_byte(caps.to_byte()), caps);
+    }
+
+    #[test]
+    fn intersection_only_keeps_shared_capabilities() {
+        let us = Capabilities::FULL_NODE | Capabilities::BLOCK_PROPAGATION;
+        let them = Capabilities::BLOCK_PROPAGATION | Capabilities::LIGHT_CLIENT;
+
+        let shared = us.intersection(them);
+        assert!(shared.contains(Capabilities::BLOCK_PROPAGATION));
+        assert!(!shared.contains(Capabilities::FULL_NODE));
+        assert!(!shared.contains(Capabilities::LIGHT_CLIENT));
+    }
+}
@@ -0,0 +1,160 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use crate::error::NetworkErr;
+use std::time::Instant;
+
+/// Base cost charged for an `AnnounceBlock` packet, regardless of contents.
+pub const ANNOUNCE_BLOCK_BASE_COST: u64 = 20;
+
+/// Base cost charged for a `RequestBlock` packet.
+pub const REQUEST_BLOCK_BASE_COST: u64 = 50;
+
+/// Base cost charged for a `SendMissingTxs` packet, before the per-tx cost.
+pub const SEND_MISSING_TXS_BASE_COST: u64 = 10;
+
+/// Cost charged for each transaction carried by a `SendMissingTxs` packet.
+pub const SEND_MISSING_TXS_PER_TX_COST: u64 = 4;
+
+/// Default buffer limit (`BL`) assigned to a freshly connected peer.
+pub const DEFAULT_BUFFER_LIMIT: u64 = 50_000;
+
+/// Default minimum recharge rate (`MRR`), expressed in buffer units per second.
+pub const DEFAULT_MIN_RECHARGE_RATE: u64 = 500;
+
+/// Returns the flow-control cost of servicing a packet of the given type.
+///
+/// `element_count` is the number of "billable" elements carried by the
+/// packet (e.g. the number of transactions in a `SendMissingTxs` packet) and
+/// is ignored for packet types whose cost doesn't scale with their payload.
+pub fn packet_cost(packet_type: u8, element_count: usize) -> u64 {
+    match packet_type {
+        10 => ANNOUNCE_BLOCK_BASE_COST,
+        11 => REQUEST_BLOCK_BASE_COST,
+        12 => SEND_MISSING_TXS_BASE_COST + SEND_MISSING_TXS_PER_TX_COST * element_count as u64,
+        _ => ANNOUNCE_BLOCK_BASE_COST,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// LES-style credit accounting for a single connected peer.
+///
+/// Each peer owns a buffer `buf` bounded by `BL` that recharges over time at
+/// rate `MRR`. Servicing a request costs some amount `C`; a peer whose
+/// buffer cannot cover `C` has its request treated as a flow-control
+/// violation instead of being serviced.
+pub struct FlowControl {
+    /// Current buffer value.
+    buf: u64,
+
+    /// Buffer limit (`BL`): the maximum value `buf` can hold.
+    bl: u64,
+
+    /// Minimum recharge rate (`MRR`), in buffer units per second.
+    mrr: u64,
+
+    /// The last time the buffer was recharged.
+    last_recharge: Instant,
+}
+
+impl FlowControl {
+    /// Creates a new `FlowControl` with a full buffer.
+    pub fn new(bl: u64, mrr: u64) -> FlowControl {
+        FlowControl {
+            buf: bl,
+            bl,
+            mrr,
+            last_recharge: Instant::now(),
+        }
+    }
+
+    /// Creates a new `FlowControl` using the default `BL`/`MRR` parameters.
+    pub fn with_defaults() -> FlowControl {
+        FlowControl::new(DEFAULT_BUFFER_LIMIT, DEFAULT_MIN_RECHARGE_RATE)
+    }
+
+    /// Returns the current buffer value, after recharging it to the present moment.
+    pub fn buf(&mut self) -> u64 {
+        self.recharge();
+        self.buf
+    }
+
+    /// Returns the buffer limit.
+    pub fn bl(&self) -> u64 {
+        self.bl
+    }
+
+    /// Recharges the buffer according to the elapsed time since the last recharge.
+    fn recharge(&mut self) {
+        let elapsed = self.last_recharge.elapsed().as_secs();
+
+        if elapsed > 0 {
+            let recharge = self.mrr.saturating_mul(elapsed);
+            self.buf = std::cmp::min(self.bl, self.buf.saturating_add(recharge));
+            self.last_recharge = Instant::now();
+        }
+    }
+
+    /// Recharges the buffer, then attempts to spend `cost` from it.
+    ///
+    /// Returns `Err(NetworkErr::FlowControlViolation)` and leaves the buffer
+    /// untouched if there isn't enough credit to cover `cost`.
+    pub fn charge(&mut self, cost: u64) -> Result<u64, NetworkErr> {
+        self.recharge();
+
+        if self.buf >= cost {
+            self.buf -= cost;
+            Ok(self.buf)
+        } else {
+            Err(NetworkErr::FlowControlViolation)
+        }
+    }
+
+    /// Mirrors a buffer value echoed back by the remote side, clamping it to `BL`.
+    pub fn mirror(&mut self, buf: u64) {
+        self.buf = std::cmp::min(self.bl, buf);
+        self.last_recharge = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_charges_and_rejects_overspend() {
+        let mut fc = FlowControl::new(100, 10);
+        assert_eq!(fc.charge(40).unwrap(), 60);
+        assert_eq!(fc.charge(60).unwrap(), 0);
+        assert!(fc.charge(1).is_err());
+    }
+
+    #[test]
+    fn it_clamps_recharge_to_buffer_limit() {
+        let mut fc = FlowControl::new(100, 10);
+        fc.mirror(100);
+        assert_eq!(fc.buf(), 100);
+    }
+
+    #[test]
+    fn send_missing_txs_cost_scales_with_tx_count() {
+        let base = packet_cost(12, 0);
+        let with_five = packet_cost(12, 5);
+        assert_eq!(with_five - base, SEND_MISSING_TXS_PER_TX_COST * 5);
+    }
+}
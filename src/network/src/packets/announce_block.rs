@@ -181,6 +181,11 @@ impl Packet for AnnounceBlock {
     fn to_client_request(&self) -> Option<ClientRequest> {
         Some(ClientRequest::AnnounceBlock)
     }
+
+    /// The flow-control cost of servicing this packet.
+    fn cost(&self) -> u64 {
+        crate::flow_control::packet_cost(Self::PACKET_TYPE, 1)
+    }
 }
 
 #[cfg(test)]
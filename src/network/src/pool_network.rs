@@ -19,11 +19,15 @@
 use crate::Peer;
 use crate::pool_peer::PoolPeer;
 use crate::error::NetworkErr;
+use crate::flow_control::{self, FlowControl};
 use crate::interface::NetworkInterface;
 use crate::packet::Packet;
 use crate::packets::connect::Connect;
 use crate::bootstrap::cache::BootstrapCache;
+use crate::capabilities::Capabilities;
 use crate::connection::*;
+use crate::reputation::{Offense, ReputationEntry, BAN_DURATION};
+use byteorder::{BigEndian, ByteOrder};
 use chain::*;
 use crypto::NodeId;
 use crypto::SecretKey as Sk;
@@ -33,6 +37,7 @@ use std::net::SocketAddr;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
+use std::time::Instant;
 
 #[cfg(test)]
 use std::sync::mpsc::Sender;
@@ -40,6 +45,31 @@ use std::sync::mpsc::Sender;
 #[cfg(not(test))]
 use futures::sync::mpsc::Sender;
 
+/// `SendMissingTxs`'s packet type, per `flow_control::packet_cost`.
+const SEND_MISSING_TXS_PACKET_TYPE: u8 = 12;
+
+/// The flow-control element count to charge `packet` for: the number of
+/// transactions it's requesting, for a `SendMissingTxs` packet, so
+/// `flow_control::SEND_MISSING_TXS_PER_TX_COST` actually scales with how
+/// much work servicing it costs a peer - or `1` for every other packet
+/// type, whose cost doesn't scale with payload.
+///
+/// The tx count is read as a big-endian `u32` from the 4 bytes right after
+/// the 1-byte packet type. A packet too short to
+/// contain a count falls back to `1` - `handle_packet` will reject it as
+/// `BadFormat` regardless, so this only affects how much the peer is
+/// charged for the rejection.
+fn billable_element_count(packet_type: u8, packet: &[u8]) -> usize {
+    if packet_type != SEND_MISSING_TXS_PACKET_TYPE {
+        return 1;
+    }
+
+    match packet.get(1..5) {
+        Some(count_bytes) => BigEndian::read_u32(count_bytes) as usize,
+        None => 1,
+    }
+}
+
 #[derive(Clone)]
 /// Separate network interface specific for validator pools.
 pub struct PoolNetwork {
@@ -57,6 +87,20 @@ pub struct PoolNetwork {
 
     /// The name of the network we are on
     pub(crate) network_name: String,
+
+    /// Per-peer LES-style buffer-flow credit accounting.
+    pub(crate) flow_control: Arc<RwLock<HashMap<SocketAddr, FlowControl>>>,
+
+    /// Per-peer misbehavior score, used to decide when to ban a peer.
+    pub(crate) reputation: Arc<RwLock<HashMap<SocketAddr, ReputationEntry>>>,
+
+    /// Addresses currently banned, mapped to the `Instant` their ban expires.
+    /// Held in memory only: bans are not written to the `BootstrapCache` and
+    /// do not survive a restart.
+    pub(crate) banned: Arc<RwLock<HashMap<SocketAddr, Instant>>>,
+
+    /// Capabilities negotiated with each connected peer during the handshake.
+    pub(crate) capabilities: Arc<RwLock<HashMap<SocketAddr, Capabilities>>>,
 }
 
 impl PoolNetwork {
@@ -72,9 +116,89 @@ impl PoolNetwork {
             port,
             network_name,
             secret_key,
+            flow_control: Arc::new(RwLock::new(HashMap::new())),
+            reputation: Arc::new(RwLock::new(HashMap::new())),
+            banned: Arc::new(RwLock::new(HashMap::new())),
+            capabilities: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Records the capabilities negotiated with `addr`. `Connect::handle`
+    /// does not call this, so a peer stays at `Capabilities::NONE` until a
+    /// caller records its capabilities here.
+    pub fn set_capabilities(&self, addr: &SocketAddr, capabilities: Capabilities) {
+        self.capabilities.write().insert(*addr, capabilities);
+    }
+
+    /// Returns the capabilities negotiated with `addr`, or `Capabilities::NONE`
+    /// if the peer hasn't completed the handshake yet.
+    pub fn capabilities_of(&self, addr: &SocketAddr) -> Capabilities {
+        self.capabilities
+            .read()
+            .get(addr)
+            .copied()
+            .unwrap_or(Capabilities::NONE)
+    }
+
+    /// Returns true if the peer with the given address is a known member of
+    /// the active validator pool, i.e. it negotiated `POOL_VALIDATOR`.
+    pub fn is_pool_member(&self, addr: &SocketAddr) -> bool {
+        self.capabilities_of(addr).contains(Capabilities::POOL_VALIDATOR)
+    }
+
+    /// Returns true if the given address is currently serving out a ban.
+    pub fn is_banned(&self, addr: &SocketAddr) -> bool {
+        match self.banned.read().get(addr) {
+            Some(expiry) => *expiry > Instant::now(),
+            None => false,
+        }
+    }
+
+    /// Reports an `offense` committed by the peer at `addr`, banning it if its
+    /// accrued score crosses `BAN_THRESHOLD`.
+    pub fn report_offense(&self, addr: &SocketAddr, offense: Offense) {
+        let banned = {
+            let mut reputation = self.reputation.write();
+            let entry = reputation.entry(*addr).or_insert_with(ReputationEntry::default);
+            entry.report(offense).1
+        };
+
+        if banned {
+            let _ = self.ban_ip(addr);
         }
     }
 
+    /// Charges the cost of servicing `packet` against the given peer's flow-control
+    /// buffer, creating a fresh `FlowControl` entry for peers seen for the first time.
+    ///
+    /// Returns the peer's remaining buffer value on success so it can be echoed back
+    /// in the corresponding response packet, or `NetworkErr::FlowControlViolation` if
+    /// the peer doesn't have enough credit.
+    pub(crate) fn charge_flow_control(
+        &self,
+        peer: &SocketAddr,
+        packet_type: u8,
+        element_count: usize,
+    ) -> Result<u64, NetworkErr> {
+        let cost = flow_control::packet_cost(packet_type, element_count);
+        let mut flow_control = self.flow_control.write();
+        let entry = flow_control
+            .entry(*peer)
+            .or_insert_with(FlowControl::with_defaults);
+
+        entry.charge(cost)
+    }
+
+    /// Mirrors a buffer value echoed back by a peer in a response packet.
+    pub(crate) fn mirror_flow_control(&self, peer: &SocketAddr, buf: u64) {
+        let mut flow_control = self.flow_control.write();
+        let entry = flow_control
+            .entry(*peer)
+            .or_insert_with(FlowControl::with_defaults);
+
+        entry.mirror(buf);
+    }
+
     /// Returns the number of listed peers.
     pub fn peer_count(&self) -> usize {
         self.peers.read().len()
@@ -247,6 +371,10 @@ impl NetworkInterface for PoolNetwork {
     }
 
     fn process_packet(&mut self, peer: &SocketAddr, packet: &[u8]) -> Result<(), NetworkErr> {
+        if self.is_banned(peer) {
+            return Err(NetworkErr::PeerIsBanned);
+        }
+
         let (is_none_id, conn_type) = {
             let peers = self.peers.read();
             let peer = peers.get(peer).unwrap();
@@ -271,10 +399,35 @@ impl NetworkInterface for PoolNetwork {
                 _ => {
                     // Invalid packet, remove peer
                     debug!("Invalid connect packet from {}", peer);
+                    self.report_offense(peer, Offense::BadFormat);
                     Err(NetworkErr::InvalidConnectPacket)
                 }
             }
         } else {
+            // Reject the request outright if the peer doesn't have enough
+            // buffer-flow credit to cover its cost, rather than servicing it.
+            if let Some(packet_type) = packet.first() {
+                // Only peers that negotiated `BLOCK_PROPAGATION` may send us
+                // block-propagation packets (currently just `AnnounceBlock`).
+                if *packet_type == crate::packets::announce_block::AnnounceBlock::PACKET_TYPE
+                    && !self
+                        .capabilities_of(peer)
+                        .contains(Capabilities::BLOCK_PROPAGATION)
+                {
+                    return Err(NetworkErr::CapabilityNotNegotiated);
+                }
+
+                let element_count = billable_element_count(*packet_type, &packet);
+
+                if self
+                    .charge_flow_control(peer, *packet_type, element_count)
+                    .is_err()
+                {
+                    self.report_offense(peer, Offense::FlowControlViolation);
+                    return Err(NetworkErr::FlowControlViolation);
+                }
+            }
+
             crate::common::handle_packet(self, conn_type, peer, &packet)?;
 
             // Refresh peer timeout timer
@@ -289,11 +442,29 @@ impl NetworkInterface for PoolNetwork {
     }
 
     fn ban_peer(&self, peer: &NodeId) -> Result<(), NetworkErr> {
-        unimplemented!();
+        let addr = {
+            let peers = self.peers.read();
+            peers
+                .iter()
+                .find(|(_, p)| p.id.as_ref() == Some(peer))
+                .map(|(addr, _)| *addr)
+        };
+
+        match addr {
+            Some(addr) => self.ban_ip(&addr),
+            None => Err(NetworkErr::PeerNotFound),
+        }
     }
 
     fn ban_ip(&self, peer: &SocketAddr) -> Result<(), NetworkErr> {
-        unimplemented!();
+        self.banned
+            .write()
+            .insert(*peer, Instant::now() + BAN_DURATION);
+        self.peers.write().remove(peer);
+        self.flow_control.write().remove(peer);
+        self.reputation.write().remove(peer);
+
+        Ok(())
     }
 
     fn our_node_id(&self) -> &NodeId {
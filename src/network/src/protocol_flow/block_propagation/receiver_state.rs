@@ -27,9 +27,23 @@ pub enum BlockReceiverState {
     /// We are waiting for a `ForwardCheckpointHeader` packet.
     WaitingCheckpoint(ShortHash, u64),
 
+    /// A light-client (`LIGHT_CLIENT` capability) is streaming a contiguous
+    /// run of `PowChainState` checkpoint headers starting at the given
+    /// height, verifying difficulty retargeting and the cuckoo-cycle proof
+    /// for each without downloading any transaction bodies. Only descends
+    /// into `WaitingTxBlock` if the client explicitly asks for a specific
+    /// block's contents.
+    WaitingHeaderChain(u64),
+
     /// We are waiting for a `ForwardTxBlockHeader` packet.
     WaitingTxBlock(ShortHash, u64),
 
+    /// We've received a Graphene-style Bloom filter plus an IBLT over the
+    /// block's txids and are running set
+    /// reconciliation against our mempool before falling back to requesting
+    /// the full list of missing transactions.
+    WaitingReconciliation(ShortHash, u64),
+
     /// We are waiting for a `SendMissingTxs` packet.
     WaitingTxs(u64),
 
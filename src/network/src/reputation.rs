@@ -0,0 +1,123 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::time::{Duration, Instant};
+
+/// The ban threshold: a peer whose score reaches this many penalty points is banned.
+pub const BAN_THRESHOLD: i64 = 100;
+
+/// How long a ban imposed by `PoolNetwork::ban_ip` lasts.
+pub const BAN_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// The rate at which penalty points decay, in points per second.
+const DECAY_PER_SECOND: i64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A concrete, typed catalogue of peer misbehavior. Each offense carries a
+/// fixed weight in penalty points so callers across the block-propagation
+/// flow can report misbehavior uniformly.
+pub enum Offense {
+    /// A packet that failed to parse in `Packet::from_bytes`.
+    BadFormat,
+
+    /// A request made while the peer had insufficient flow-control credit;
+    /// the request is rejected rather than serviced.
+    FlowControlViolation,
+
+    /// The `BlockReceiverState` machine timed out waiting for a follow-up
+    /// packet (`WaitingCheckpoint`/`WaitingTxBlock`/`WaitingTxs`).
+    StateMachineTimeout,
+
+    /// The peer served a block that failed validation.
+    InvalidBlock,
+}
+
+impl Offense {
+    /// The penalty points charged for this offense.
+    pub fn weight(&self) -> i64 {
+        match self {
+            Offense::BadFormat => 10,
+            Offense::FlowControlViolation => 15,
+            Offense::StateMachineTimeout => 20,
+            Offense::InvalidBlock => 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Tracks a single peer's accrued misbehavior score, decaying it over time so
+/// that transient issues don't permanently brand a peer.
+pub struct ReputationEntry {
+    score: i64,
+    last_decay: Instant,
+}
+
+impl Default for ReputationEntry {
+    fn default() -> Self {
+        ReputationEntry {
+            score: 0,
+            last_decay: Instant::now(),
+        }
+    }
+}
+
+impl ReputationEntry {
+    /// Applies time-based decay, then records `offense`. Returns the
+    /// resulting score and whether it has crossed `BAN_THRESHOLD`.
+    pub fn report(&mut self, offense: Offense) -> (i64, bool) {
+        self.decay();
+        self.score += offense.weight();
+        (self.score, self.score >= BAN_THRESHOLD)
+    }
+
+    /// Returns the current score, after applying decay.
+    pub fn score(&mut self) -> i64 {
+        self.decay();
+        self.score
+    }
+
+    fn decay(&mut self) {
+        let elapsed = self.last_decay.elapsed().as_secs() as i64;
+
+        if elapsed > 0 {
+            self.score = (self.score - elapsed * DECAY_PER_SECOND).max(0);
+            self.last_decay = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_bans_after_crossing_the_threshold() {
+        let mut entry = ReputationEntry::default();
+
+        let (_, banned) = entry.report(Offense::InvalidBlock);
+        assert!(!banned);
+
+        let (_, banned) = entry.report(Offense::InvalidBlock);
+        assert!(banned);
+    }
+
+    #[test]
+    fn weights_rank_invalid_block_above_bad_format() {
+        assert!(Offense::InvalidBlock.weight() > Offense::BadFormat.weight());
+    }
+}
@@ -0,0 +1,113 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Compares `VmValue` array `Add`/`Sub`/`Mul` with the `simd` feature on
+//! against the portable scalar loop (the `simd` feature off), across every
+//! lane width, so the "benchmark first" this used to defer on has an answer.
+//! Run with `cargo bench --bench value_arith` (add `--features simd` to
+//! measure the vectorized path; without it every case exercises the scalar
+//! fallback).
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use purple_vm::primitives::value::VmValue;
+
+macro_rules! bench_arm {
+    ($group:expr, $label:expr, $variant:ident, $n:expr, $a:expr, $b_val:expr, $op:tt) => {
+        $group.bench_with_input(BenchmarkId::new($label, $n), &$n, |bencher, _| {
+            let val1 = VmValue::$variant([$a; $n]);
+            let val2 = VmValue::$variant([$b_val; $n]);
+
+            bencher.iter(|| black_box(val1) $op black_box(val2))
+        });
+    };
+}
+
+fn add_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("VmValue::add");
+
+    bench_arm!(group, "i32", i32Array2, 2, 1, 2, +);
+    bench_arm!(group, "i32", i32Array4, 4, 1, 2, +);
+    bench_arm!(group, "i32", i32Array8, 8, 1, 2, +);
+    bench_arm!(group, "i32", i32Array16, 16, 1, 2, +);
+    bench_arm!(group, "i32", i32Array32, 32, 1, 2, +);
+    bench_arm!(group, "i32", i32Array64, 64, 1, 2, +);
+    bench_arm!(group, "i32", i32Array128, 128, 1, 2, +);
+    bench_arm!(group, "i32", i32Array256, 256, 1, 2, +);
+
+    bench_arm!(group, "i64", i64Array2, 2, 1i64, 2i64, +);
+    bench_arm!(group, "i64", i64Array4, 4, 1i64, 2i64, +);
+    bench_arm!(group, "i64", i64Array8, 8, 1i64, 2i64, +);
+    bench_arm!(group, "i64", i64Array16, 16, 1i64, 2i64, +);
+    bench_arm!(group, "i64", i64Array32, 32, 1i64, 2i64, +);
+    bench_arm!(group, "i64", i64Array64, 64, 1i64, 2i64, +);
+    bench_arm!(group, "i64", i64Array128, 128, 1i64, 2i64, +);
+    bench_arm!(group, "i64", i64Array256, 256, 1i64, 2i64, +);
+
+    group.finish();
+}
+
+fn sub_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("VmValue::sub");
+
+    bench_arm!(group, "i32", i32Array2, 2, 5, 1, -);
+    bench_arm!(group, "i32", i32Array4, 4, 5, 1, -);
+    bench_arm!(group, "i32", i32Array8, 8, 5, 1, -);
+    bench_arm!(group, "i32", i32Array16, 16, 5, 1, -);
+    bench_arm!(group, "i32", i32Array32, 32, 5, 1, -);
+    bench_arm!(group, "i32", i32Array64, 64, 5, 1, -);
+    bench_arm!(group, "i32", i32Array128, 128, 5, 1, -);
+    bench_arm!(group, "i32", i32Array256, 256, 5, 1, -);
+
+    bench_arm!(group, "i64", i64Array2, 2, 5i64, 1i64, -);
+    bench_arm!(group, "i64", i64Array4, 4, 5i64, 1i64, -);
+    bench_arm!(group, "i64", i64Array8, 8, 5i64, 1i64, -);
+    bench_arm!(group, "i64", i64Array16, 16, 5i64, 1i64, -);
+    bench_arm!(group, "i64", i64Array32, 32, 5i64, 1i64, -);
+    bench_arm!(group, "i64", i64Array64, 64, 5i64, 1i64, -);
+    bench_arm!(group, "i64", i64Array128, 128, 5i64, 1i64, -);
+    bench_arm!(group, "i64", i64Array256, 256, 5i64, 1i64, -);
+
+    group.finish();
+}
+
+fn mul_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("VmValue::mul");
+
+    bench_arm!(group, "i32", i32Array2, 2, 3, 2, *);
+    bench_arm!(group, "i32", i32Array4, 4, 3, 2, *);
+    bench_arm!(group, "i32", i32Array8, 8, 3, 2, *);
+    bench_arm!(group, "i32", i32Array16, 16, 3, 2, *);
+    bench_arm!(group, "i32", i32Array32, 32, 3, 2, *);
+    bench_arm!(group, "i32", i32Array64, 64, 3, 2, *);
+    bench_arm!(group, "i32", i32Array128, 128, 3, 2, *);
+    bench_arm!(group, "i32", i32Array256, 256, 3, 2, *);
+
+    bench_arm!(group, "i64", i64Array2, 2, 3i64, 2i64, *);
+    bench_arm!(group, "i64", i64Array4, 4, 3i64, 2i64, *);
+    bench_arm!(group, "i64", i64Array8, 8, 3i64, 2i64, *);
+    bench_arm!(group, "i64", i64Array16, 16, 3i64, 2i64, *);
+    bench_arm!(group, "i64", i64Array32, 32, 3i64, 2i64, *);
+    bench_arm!(group, "i64", i64Array64, 64, 3i64, 2i64, *);
+    bench_arm!(group, "i64", i64Array128, 128, 3i64, 2i64, *);
+    bench_arm!(group, "i64", i64Array256, 256, 3i64, 2i64, *);
+
+    group.finish();
+}
+
+criterion_group!(benches, add_benchmark, sub_benchmark, mul_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,241 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Field arithmetic over GF(2^255 - 19), the field curve25519/Ed25519 point
+//! and scalar operations are built on. A field element is five 51-bit limbs,
+//! little-endian: `val = limbs[0] + limbs[1]*2^51 + ... + limbs[4]*2^204`.
+//!
+//! `add`/`sub`/`mul` are loosely reduced (limbs stay under 2^51 but the value
+//! they represent may sit anywhere in `[0, 2*p)`); only `freeze` (used by
+//! equality and `Debug`) produces the canonical representative in `[0, p)`.
+//! `add`/`sub`/`mul` are branch-free and take the same number of operations
+//! regardless of the limb values, so a contract doing field math over secret
+//! values (Schnorr/Ed25519 verification helpers) doesn't leak it through
+//! timing. `freeze` is not constant-time, since it only ever runs on values
+//! about to be compared or printed, never on a value still being computed on.
+
+use VmError;
+
+pub type Limbs = [u64; 5];
+
+const MASK: u64 = (1 << 51) - 1;
+
+/// The field modulus `p = 2^255 - 19`, as five 51-bit limbs.
+const P: Limbs = [MASK - 18, MASK, MASK, MASK, MASK];
+
+/// `2*p`, used by `sub`'s "add `2*p` then subtract" trick so the limb-wise
+/// subtraction below never has to borrow.
+const TWO_P: Limbs = [(1 << 52) - 38, (1 << 52) - 2, (1 << 52) - 2, (1 << 52) - 2, (1 << 52) - 2];
+
+pub fn zero() -> Limbs {
+    [0; 5]
+}
+
+/// Two-pass carry chain: the first pass pushes each limb's overflow into the
+/// next, the second folds limb 4's overflow back into limb 0 multiplied by 19
+/// (since `2^255 = 19 (mod p)`), then re-normalizes the one limb that touched.
+fn carry(t: &mut [u128; 5]) -> Limbs {
+    for i in 0..4 {
+        let c = t[i] >> 51;
+        t[i] &= MASK as u128;
+        t[i + 1] += c;
+    }
+
+    let c = t[4] >> 51;
+    t[4] &= MASK as u128;
+    t[0] += c * 19;
+
+    let mut r = [0u64; 5];
+    for i in 0..5 {
+        r[i] = t[i] as u64;
+    }
+
+    let c = r[0] >> 51;
+    r[0] &= MASK;
+    r[1] += c;
+
+    r
+}
+
+/// Limb-wise addition followed by the shared carry chain.
+pub fn add(a: &Limbs, b: &Limbs) -> Limbs {
+    let mut t = [0u128; 5];
+    for i in 0..5 {
+        t[i] = a[i] as u128 + b[i] as u128;
+    }
+
+    carry(&mut t)
+}
+
+/// Subtraction via "add `2*p`, then subtract `b` limb-wise": `a` is already
+/// `< 2*p` (loosely reduced), so `a + 2*p - b` is guaranteed non-negative
+/// without ever inspecting a sign, and the usual carry chain normalizes it.
+pub fn sub(a: &Limbs, b: &Limbs) -> Limbs {
+    let mut t = [0u128; 5];
+    for i in 0..5 {
+        t[i] = a[i] as u128 + TWO_P[i] as u128 - b[i] as u128;
+    }
+
+    carry(&mut t)
+}
+
+/// Schoolbook 5x5 limb product with the `19*` reduction for terms that land
+/// at or above limb index 5 folded directly into the corresponding low limb
+/// (since `2^(51*5) = 2^255 = 19 (mod p)`), followed by the shared carry
+/// chain.
+pub fn mul(a: &Limbs, b: &Limbs) -> Limbs {
+    let b1_19 = 19 * b[1] as u128;
+    let b2_19 = 19 * b[2] as u128;
+    let b3_19 = 19 * b[3] as u128;
+    let b4_19 = 19 * b[4] as u128;
+
+    let a0 = a[0] as u128;
+    let a1 = a[1] as u128;
+    let a2 = a[2] as u128;
+    let a3 = a[3] as u128;
+    let a4 = a[4] as u128;
+
+    let b0 = b[0] as u128;
+    let b1 = b[1] as u128;
+    let b2 = b[2] as u128;
+    let b3 = b[3] as u128;
+    let b4 = b[4] as u128;
+
+    let mut t = [
+        a0 * b0 + a1 * b4_19 + a2 * b3_19 + a3 * b2_19 + a4 * b1_19,
+        a0 * b1 + a1 * b0 + a2 * b4_19 + a3 * b3_19 + a4 * b2_19,
+        a0 * b2 + a1 * b1 + a2 * b0 + a3 * b4_19 + a4 * b3_19,
+        a0 * b3 + a1 * b2 + a2 * b1 + a3 * b0 + a4 * b4_19,
+        a0 * b4 + a1 * b3 + a2 * b2 + a3 * b1 + a4 * b0,
+    ];
+
+    carry(&mut t)
+}
+
+fn square(a: &Limbs) -> Limbs {
+    mul(a, a)
+}
+
+/// Squares `a` `k` times in a row.
+fn pow2k(a: &Limbs, k: u32) -> Limbs {
+    let mut r = square(a);
+    for _ in 1..k {
+        r = square(&r);
+    }
+    r
+}
+
+/// Modular inverse via Fermat's little theorem (`a^(p-2) mod p`), computed
+/// with the standard curve25519 fixed addition chain for the exponent
+/// `p - 2 = 2^255 - 21` (the same chain used by ref10/dalek's `fe_invert`):
+/// 254 squarings and 11 multiplications, always in the same order regardless
+/// of `a`, so the running time never depends on the secret value.
+pub fn invert(a: &Limbs) -> Limbs {
+    let z2 = square(a);
+    let z8 = square(&square(&z2));
+    let z9 = mul(&z8, a);
+    let z11 = mul(&z9, &z2);
+    let z22 = square(&z11);
+    let z_5_0 = mul(&z22, &z9);
+
+    let z_10_5 = pow2k(&z_5_0, 5);
+    let z_10_0 = mul(&z_10_5, &z_5_0);
+
+    let z_20_10 = pow2k(&z_10_0, 10);
+    let z_20_0 = mul(&z_20_10, &z_10_0);
+
+    let z_40_20 = pow2k(&z_20_0, 20);
+    let z_40_0 = mul(&z_40_20, &z_20_0);
+
+    let z_50_10 = pow2k(&z_40_0, 10);
+    let z_50_0 = mul(&z_50_10, &z_10_0);
+
+    let z_100_50 = pow2k(&z_50_0, 50);
+    let z_100_0 = mul(&z_100_50, &z_50_0);
+
+    let z_200_100 = pow2k(&z_100_0, 100);
+    let z_200_0 = mul(&z_200_100, &z_100_0);
+
+    let z_250_50 = pow2k(&z_200_0, 50);
+    let z_250_0 = mul(&z_250_50, &z_50_0);
+
+    let z_255_5 = pow2k(&z_250_0, 5);
+    mul(&z_255_5, &z11)
+}
+
+fn limbs_ge(a: &Limbs, b: &Limbs) -> bool {
+    for i in (0..5).rev() {
+        if a[i] != b[i] {
+            return a[i] > b[i];
+        }
+    }
+    true
+}
+
+fn sub_raw(a: &Limbs, b: &Limbs) -> Limbs {
+    let mut r = [0u64; 5];
+    let mut borrow = 0i64;
+    for i in 0..5 {
+        let diff = a[i] as i64 - b[i] as i64 - borrow;
+        if diff < 0 {
+            r[i] = (diff + (1i64 << 51)) as u64;
+            borrow = 1;
+        } else {
+            r[i] = diff as u64;
+            borrow = 0;
+        }
+    }
+    r
+}
+
+/// Reduces `a` to its canonical representative in `[0, p)`. Only used by
+/// equality comparison and `Debug` formatting, never on the hot path of an
+/// arithmetic op, so it doesn't need to be constant-time.
+pub fn freeze(a: &Limbs) -> Limbs {
+    let mut t = [0u128; 5];
+    for i in 0..5 {
+        t[i] = a[i] as u128;
+    }
+    let mut r = carry(&mut t);
+
+    for _ in 0..2 {
+        if limbs_ge(&r, &P) {
+            r = sub_raw(&r, &P);
+        }
+    }
+
+    r
+}
+
+pub fn is_zero(a: &Limbs) -> bool {
+    freeze(a) == zero()
+}
+
+pub fn eq(a: &Limbs, b: &Limbs) -> bool {
+    freeze(a) == freeze(b)
+}
+
+/// Field division: `a / b = a * b^-1`, so the only way to hit
+/// `VmError::DivideByZero` is a genuine zero divisor.
+pub fn div(a: &Limbs, b: &Limbs) -> Result<Limbs, VmError> {
+    if is_zero(b) {
+        return Err(VmError::DivideByZero);
+    }
+
+    Ok(mul(a, &invert(b)))
+}
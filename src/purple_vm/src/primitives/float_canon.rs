@@ -0,0 +1,102 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Float-NaN canonicalization on top of `validate_structure`, for consensus
+//! paths where "is this bit-decodable" isn't a strong enough guarantee.
+//!
+//! `validate_structure` happily accepts any bit-decodable float, including
+//! the huge space of NaN payloads and signaling NaNs IEEE-754 allows - all
+//! "valid" floats, but not the same bit pattern, so they'd hash differently
+//! (and some platforms even flip signaling NaNs to quiet ones on ordinary
+//! arithmetic) despite representing the same "not a number". A consensus
+//! system can't tolerate that: two honest nodes computing "the same" float
+//! must end up with the same bytes. `validate_structure_canonical` adds
+//! that guarantee: any non-NaN value passes through unchanged, but a NaN is
+//! only valid if it's bit-for-bit the canonical quiet NaN for its width.
+
+use super::r#type::VmType;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::Cursor;
+
+/// Canonical quiet-NaN bit pattern: sign 0, every exponent bit set, top
+/// mantissa bit set, every other mantissa bit clear.
+const CANONICAL_NAN_F16: u16 = 0x7E00;
+const CANONICAL_NAN_F32: u32 = 0x7FC0_0000;
+const CANONICAL_NAN_F64: u64 = 0x7FF8_0000_0000_0000;
+
+fn is_nan_f32(bits: u32) -> bool {
+    (bits & 0x7F80_0000) == 0x7F80_0000 && (bits & 0x007F_FFFF) != 0
+}
+
+fn is_nan_f64(bits: u64) -> bool {
+    (bits & 0x7FF0_0000_0000_0000) == 0x7FF0_0000_0000_0000
+        && (bits & 0x000F_FFFF_FFFF_FFFF) != 0
+}
+
+fn is_nan_f16(bits: u16) -> bool {
+    (bits & 0x7C00) == 0x7C00 && (bits & 0x03FF) != 0
+}
+
+impl VmType {
+    /// Like `validate_structure`, but additionally rejects any non-canonical
+    /// NaN in a float (or float array) lane: a NaN lane must be exactly
+    /// `CANONICAL_NAN_F32`/`CANONICAL_NAN_F64`/`CANONICAL_NAN_F16` for its
+    /// width. Non-float types, and non-NaN float values, behave exactly
+    /// like `validate_structure`.
+    pub fn validate_structure_canonical(&self, buf: &[u8]) -> bool {
+        if !self.validate_structure(buf) {
+            return false;
+        }
+
+        if !self.is_float() {
+            return true;
+        }
+
+        let lane = match *self {
+            VmType::Vector(elem, _) => elem.as_vm_type(),
+            _ => self.array_accepts().unwrap_or(*self),
+        };
+
+        match lane {
+            VmType::F32 => all_lanes(buf, 4, |c| {
+                c.read_u32::<BigEndian>()
+                    .map(|bits| !is_nan_f32(bits) || bits == CANONICAL_NAN_F32)
+                    .unwrap_or(false)
+            }),
+            VmType::F64 => all_lanes(buf, 8, |c| {
+                c.read_u64::<BigEndian>()
+                    .map(|bits| !is_nan_f64(bits) || bits == CANONICAL_NAN_F64)
+                    .unwrap_or(false)
+            }),
+            VmType::F16 => all_lanes(buf, 2, |c| {
+                c.read_u16::<BigEndian>()
+                    .map(|bits| !is_nan_f16(bits) || bits == CANONICAL_NAN_F16)
+                    .unwrap_or(false)
+            }),
+            _ => unreachable!("is_float() only admits F32/F64/F16 lane types"),
+        }
+    }
+}
+
+/// Runs `check` over every `lane_bytes`-wide chunk of `buf`, requiring all
+/// of them to pass. `buf`'s length is already known to be a multiple of
+/// `lane_bytes`, since `validate_structure` passed before this is called.
+fn all_lanes(buf: &[u8], lane_bytes: usize, check: impl Fn(&mut Cursor<&[u8]>) -> bool) -> bool {
+    buf.chunks_exact(lane_bytes)
+        .all(|chunk| check(&mut Cursor::new(chunk)))
+}
@@ -0,0 +1,80 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Metered dispatch for `VmValue`'s arithmetic opcodes: a `FuelContext`
+//! carries the remaining `fuel` budget and an optional trace hook, and
+//! `VmValue::mul_metered`/`div_metered`/`rem_metered` (see `value.rs`) charge
+//! a per-opcode cost and run the trace hook before falling through to the
+//! existing `impl Mul`/`Div`/`Rem` bodies. Bare `*`/`/`/`%` on `VmValue`
+//! still work unmetered, exactly as before; the metered entry points are for
+//! a VM driver loop that wants deterministic gas limits and step-by-step
+//! debugging instead.
+
+use super::value::VmValue;
+use VmError;
+
+/// Called with the opcode name and both operands just before the opcode
+/// actually executes; returning `false` halts the machine with
+/// `VmError::Halted` instead of letting the opcode run.
+pub type TraceHandler = dyn FnMut(&'static str, VmValue, VmValue) -> bool;
+
+pub struct FuelContext {
+    fuel: u64,
+    trace_handler: Option<Box<TraceHandler>>,
+}
+
+impl FuelContext {
+    /// A context with `fuel` to spend and no trace hook.
+    pub fn new(fuel: u64) -> Self {
+        FuelContext {
+            fuel,
+            trace_handler: None,
+        }
+    }
+
+    /// A context with `fuel` to spend and a trace hook invoked before every
+    /// metered opcode.
+    pub fn with_trace_handler(fuel: u64, trace_handler: Box<TraceHandler>) -> Self {
+        FuelContext {
+            fuel,
+            trace_handler: Some(trace_handler),
+        }
+    }
+
+    pub fn remaining_fuel(&self) -> u64 {
+        self.fuel
+    }
+
+    /// Deducts `cost` from the remaining fuel, failing with
+    /// `VmError::OutOfFuel` instead of underflowing if it would go negative.
+    pub(crate) fn charge(&mut self, cost: u64) -> Result<(), VmError> {
+        self.fuel = self.fuel.checked_sub(cost).ok_or(VmError::OutOfFuel)?;
+        Ok(())
+    }
+
+    /// Runs the trace hook, if any, ahead of the opcode it was attached to.
+    pub(crate) fn trace(&mut self, op: &'static str, val1: VmValue, val2: VmValue) -> Result<(), VmError> {
+        if let Some(handler) = self.trace_handler.as_mut() {
+            if !handler(op, val1, val2) {
+                return Err(VmError::Halted);
+            }
+        }
+
+        Ok(())
+    }
+}
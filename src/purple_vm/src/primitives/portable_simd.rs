@@ -0,0 +1,538 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! `core::simd` (portable-SIMD) backend for `VmValue`'s array `Mul`/`Div`/
+//! `Rem`, kept separate from `simd_arith` (which stays on arch-specific
+//! `std::arch` intrinsics for `Add`/`Sub`) because the overflow and
+//! divide-by-zero masking these three ops need is naturally expressed with
+//! `core::simd`'s lane comparison/mask API instead of hand-rolled intrinsics.
+//! Requires the nightly `portable_simd` feature enabled at the crate root.
+//!
+//! Like `simd_arith`, every function here takes whole slices rather than a
+//! fixed width, processing `LANES_I32`/`LANES_I64` elements at a time and
+//! falling back to the scalar loop for whatever doesn't divide evenly (in
+//! practice only the 2-lane `i32`/`f32` arrays, since every other width here
+//! is a multiple of 4).
+
+#[cfg(feature = "simd")]
+use std::simd::{Simd, SimdFloat, SimdInt, SimdPartialEq, SimdPartialOrd};
+use VmError;
+
+#[cfg(feature = "simd")]
+mod vector {
+    use super::*;
+
+    const LANES_I32: usize = 4;
+    const LANES_I64: usize = 2;
+    const LANES_F32: usize = 4;
+    const LANES_F64: usize = 2;
+
+    /// Widens each lane to `i64`, multiplies, then masks the product against
+    /// `i32`'s representable range to detect per-lane overflow before
+    /// narrowing back down, matching `checked_mul`'s contract exactly.
+    pub fn mul_i32(a: &[i32], b: &[i32], out: &mut [i32]) -> Result<(), VmError> {
+        let mut ca = a.chunks_exact(LANES_I32);
+        let mut cb = b.chunks_exact(LANES_I32);
+        let mut co = out.chunks_exact_mut(LANES_I32);
+
+        for ((xa, xb), xo) in (&mut ca).zip(&mut cb).zip(&mut co) {
+            let va = Simd::<i32, LANES_I32>::from_slice(xa).cast::<i64>();
+            let vb = Simd::<i32, LANES_I32>::from_slice(xb).cast::<i64>();
+            let product = va * vb;
+            let lo = Simd::splat(i32::MIN as i64);
+            let hi = Simd::splat(i32::MAX as i64);
+
+            if product.simd_lt(lo).any() || product.simd_gt(hi).any() {
+                return Err(VmError::Overflow);
+            }
+
+            xo.copy_from_slice(product.cast::<i32>().as_array());
+        }
+
+        for ((xa, xb), xo) in ca
+            .remainder()
+            .iter()
+            .zip(cb.remainder())
+            .zip(co.into_remainder())
+        {
+            *xo = xa.checked_mul(*xb).ok_or(VmError::Overflow)?;
+        }
+
+        Ok(())
+    }
+
+    /// Lane-masks the zero divisor and the `i32::MIN / -1` overflow case
+    /// before issuing the vector divide, so this never hits the hardware
+    /// trap either case would otherwise raise.
+    pub fn div_i32(a: &[i32], b: &[i32], out: &mut [i32]) -> Result<(), VmError> {
+        let mut ca = a.chunks_exact(LANES_I32);
+        let mut cb = b.chunks_exact(LANES_I32);
+        let mut co = out.chunks_exact_mut(LANES_I32);
+
+        for ((xa, xb), xo) in (&mut ca).zip(&mut cb).zip(&mut co) {
+            let va = Simd::<i32, LANES_I32>::from_slice(xa);
+            let vb = Simd::<i32, LANES_I32>::from_slice(xb);
+
+            if vb.simd_eq(Simd::splat(0)).any() {
+                return Err(VmError::DivideByZero);
+            }
+
+            let overflow = va.simd_eq(Simd::splat(i32::MIN)) & vb.simd_eq(Simd::splat(-1));
+            if overflow.any() {
+                return Err(VmError::Overflow);
+            }
+
+            xo.copy_from_slice((va / vb).as_array());
+        }
+
+        for ((xa, xb), xo) in ca
+            .remainder()
+            .iter()
+            .zip(cb.remainder())
+            .zip(co.into_remainder())
+        {
+            if *xb == 0 {
+                return Err(VmError::DivideByZero);
+            }
+            *xo = xa.checked_div(*xb).ok_or(VmError::Overflow)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn rem_i32(a: &[i32], b: &[i32], out: &mut [i32]) -> Result<(), VmError> {
+        let mut ca = a.chunks_exact(LANES_I32);
+        let mut cb = b.chunks_exact(LANES_I32);
+        let mut co = out.chunks_exact_mut(LANES_I32);
+
+        for ((xa, xb), xo) in (&mut ca).zip(&mut cb).zip(&mut co) {
+            let va = Simd::<i32, LANES_I32>::from_slice(xa);
+            let vb = Simd::<i32, LANES_I32>::from_slice(xb);
+
+            if vb.simd_eq(Simd::splat(0)).any() {
+                return Err(VmError::DivideByZero);
+            }
+
+            let overflow = va.simd_eq(Simd::splat(i32::MIN)) & vb.simd_eq(Simd::splat(-1));
+            if overflow.any() {
+                return Err(VmError::Overflow);
+            }
+
+            xo.copy_from_slice((va % vb).as_array());
+        }
+
+        for ((xa, xb), xo) in ca
+            .remainder()
+            .iter()
+            .zip(cb.remainder())
+            .zip(co.into_remainder())
+        {
+            if *xb == 0 {
+                return Err(VmError::DivideByZero);
+            }
+            *xo = xa.checked_rem(*xb).ok_or(VmError::Overflow)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn div_i64(a: &[i64], b: &[i64], out: &mut [i64]) -> Result<(), VmError> {
+        let mut ca = a.chunks_exact(LANES_I64);
+        let mut cb = b.chunks_exact(LANES_I64);
+        let mut co = out.chunks_exact_mut(LANES_I64);
+
+        for ((xa, xb), xo) in (&mut ca).zip(&mut cb).zip(&mut co) {
+            let va = Simd::<i64, LANES_I64>::from_slice(xa);
+            let vb = Simd::<i64, LANES_I64>::from_slice(xb);
+
+            if vb.simd_eq(Simd::splat(0)).any() {
+                return Err(VmError::DivideByZero);
+            }
+
+            let overflow = va.simd_eq(Simd::splat(i64::MIN)) & vb.simd_eq(Simd::splat(-1));
+            if overflow.any() {
+                return Err(VmError::Overflow);
+            }
+
+            xo.copy_from_slice((va / vb).as_array());
+        }
+
+        debug_assert!(ca.remainder().is_empty(), "i64 widths are all multiples of 2");
+        Ok(())
+    }
+
+    pub fn rem_i64(a: &[i64], b: &[i64], out: &mut [i64]) -> Result<(), VmError> {
+        let mut ca = a.chunks_exact(LANES_I64);
+        let mut cb = b.chunks_exact(LANES_I64);
+        let mut co = out.chunks_exact_mut(LANES_I64);
+
+        for ((xa, xb), xo) in (&mut ca).zip(&mut cb).zip(&mut co) {
+            let va = Simd::<i64, LANES_I64>::from_slice(xa);
+            let vb = Simd::<i64, LANES_I64>::from_slice(xb);
+
+            if vb.simd_eq(Simd::splat(0)).any() {
+                return Err(VmError::DivideByZero);
+            }
+
+            let overflow = va.simd_eq(Simd::splat(i64::MIN)) & vb.simd_eq(Simd::splat(-1));
+            if overflow.any() {
+                return Err(VmError::Overflow);
+            }
+
+            xo.copy_from_slice((va % vb).as_array());
+        }
+
+        debug_assert!(ca.remainder().is_empty(), "i64 widths are all multiples of 2");
+        Ok(())
+    }
+
+    pub fn div_f32(a: &[f32], b: &[f32], out: &mut [f32]) -> Result<(), VmError> {
+        let mut ca = a.chunks_exact(LANES_F32);
+        let mut cb = b.chunks_exact(LANES_F32);
+        let mut co = out.chunks_exact_mut(LANES_F32);
+
+        for ((xa, xb), xo) in (&mut ca).zip(&mut cb).zip(&mut co) {
+            let va = Simd::<f32, LANES_F32>::from_slice(xa);
+            let vb = Simd::<f32, LANES_F32>::from_slice(xb);
+
+            if vb.simd_eq(Simd::splat(0.0)).any() {
+                return Err(VmError::DivideByZero);
+            }
+
+            let result = va / vb;
+            if result.is_infinite().any() {
+                return Err(VmError::Infinity);
+            }
+
+            xo.copy_from_slice(result.as_array());
+        }
+
+        for ((xa, xb), xo) in ca
+            .remainder()
+            .iter()
+            .zip(cb.remainder())
+            .zip(co.into_remainder())
+        {
+            if *xb == 0.0 {
+                return Err(VmError::DivideByZero);
+            }
+            let result = xa / xb;
+            if result.is_infinite() {
+                return Err(VmError::Infinity);
+            }
+            *xo = result;
+        }
+
+        Ok(())
+    }
+
+    pub fn rem_f32(a: &[f32], b: &[f32], out: &mut [f32]) -> Result<(), VmError> {
+        let mut ca = a.chunks_exact(LANES_F32);
+        let mut cb = b.chunks_exact(LANES_F32);
+        let mut co = out.chunks_exact_mut(LANES_F32);
+
+        for ((xa, xb), xo) in (&mut ca).zip(&mut cb).zip(&mut co) {
+            let va = Simd::<f32, LANES_F32>::from_slice(xa);
+            let vb = Simd::<f32, LANES_F32>::from_slice(xb);
+
+            if vb.simd_eq(Simd::splat(0.0)).any() {
+                return Err(VmError::DivideByZero);
+            }
+
+            let result = va % vb;
+            if result.is_infinite().any() {
+                return Err(VmError::Infinity);
+            }
+
+            xo.copy_from_slice(result.as_array());
+        }
+
+        for ((xa, xb), xo) in ca
+            .remainder()
+            .iter()
+            .zip(cb.remainder())
+            .zip(co.into_remainder())
+        {
+            if *xb == 0.0 {
+                return Err(VmError::DivideByZero);
+            }
+            let result = xa % xb;
+            if result.is_infinite() {
+                return Err(VmError::Infinity);
+            }
+            *xo = result;
+        }
+
+        Ok(())
+    }
+
+    pub fn div_f64(a: &[f64], b: &[f64], out: &mut [f64]) -> Result<(), VmError> {
+        let mut ca = a.chunks_exact(LANES_F64);
+        let mut cb = b.chunks_exact(LANES_F64);
+        let mut co = out.chunks_exact_mut(LANES_F64);
+
+        for ((xa, xb), xo) in (&mut ca).zip(&mut cb).zip(&mut co) {
+            let va = Simd::<f64, LANES_F64>::from_slice(xa);
+            let vb = Simd::<f64, LANES_F64>::from_slice(xb);
+
+            if vb.simd_eq(Simd::splat(0.0)).any() {
+                return Err(VmError::DivideByZero);
+            }
+
+            let result = va / vb;
+            if result.is_infinite().any() {
+                return Err(VmError::Infinity);
+            }
+
+            xo.copy_from_slice(result.as_array());
+        }
+
+        debug_assert!(ca.remainder().is_empty(), "f64 widths are all multiples of 2");
+        Ok(())
+    }
+
+    pub fn rem_f64(a: &[f64], b: &[f64], out: &mut [f64]) -> Result<(), VmError> {
+        let mut ca = a.chunks_exact(LANES_F64);
+        let mut cb = b.chunks_exact(LANES_F64);
+        let mut co = out.chunks_exact_mut(LANES_F64);
+
+        for ((xa, xb), xo) in (&mut ca).zip(&mut cb).zip(&mut co) {
+            let va = Simd::<f64, LANES_F64>::from_slice(xa);
+            let vb = Simd::<f64, LANES_F64>::from_slice(xb);
+
+            if vb.simd_eq(Simd::splat(0.0)).any() {
+                return Err(VmError::DivideByZero);
+            }
+
+            let result = va % vb;
+            if result.is_infinite().any() {
+                return Err(VmError::Infinity);
+            }
+
+            xo.copy_from_slice(result.as_array());
+        }
+
+        debug_assert!(ca.remainder().is_empty(), "f64 widths are all multiples of 2");
+        Ok(())
+    }
+}
+
+/// Plain per-lane scalar loops, identical to what `Div`/`Rem`'s array arms
+/// used before this change; this is the fallback when the `simd` feature is
+/// off, and (for `i64` multiply) even when it's on, since neither
+/// `std::arch` nor `core::simd` has a native 64x64-bit widening multiply to
+/// check against.
+mod scalar {
+    use super::*;
+
+    pub fn mul_i32(a: &[i32], b: &[i32], out: &mut [i32]) -> Result<(), VmError> {
+        for ((xa, xb), xo) in a.iter().zip(b).zip(out) {
+            *xo = xa.checked_mul(*xb).ok_or(VmError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn mul_i64(a: &[i64], b: &[i64], out: &mut [i64]) -> Result<(), VmError> {
+        for ((xa, xb), xo) in a.iter().zip(b).zip(out) {
+            *xo = xa.checked_mul(*xb).ok_or(VmError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn div_i32(a: &[i32], b: &[i32], out: &mut [i32]) -> Result<(), VmError> {
+        for ((xa, xb), xo) in a.iter().zip(b).zip(out) {
+            if *xb == 0 {
+                return Err(VmError::DivideByZero);
+            }
+            *xo = xa.checked_div(*xb).ok_or(VmError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn rem_i32(a: &[i32], b: &[i32], out: &mut [i32]) -> Result<(), VmError> {
+        for ((xa, xb), xo) in a.iter().zip(b).zip(out) {
+            if *xb == 0 {
+                return Err(VmError::DivideByZero);
+            }
+            *xo = xa.checked_rem(*xb).ok_or(VmError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn div_i64(a: &[i64], b: &[i64], out: &mut [i64]) -> Result<(), VmError> {
+        for ((xa, xb), xo) in a.iter().zip(b).zip(out) {
+            if *xb == 0 {
+                return Err(VmError::DivideByZero);
+            }
+            *xo = xa.checked_div(*xb).ok_or(VmError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn rem_i64(a: &[i64], b: &[i64], out: &mut [i64]) -> Result<(), VmError> {
+        for ((xa, xb), xo) in a.iter().zip(b).zip(out) {
+            if *xb == 0 {
+                return Err(VmError::DivideByZero);
+            }
+            *xo = xa.checked_rem(*xb).ok_or(VmError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn div_f32(a: &[f32], b: &[f32], out: &mut [f32]) -> Result<(), VmError> {
+        for ((xa, xb), xo) in a.iter().zip(b).zip(out) {
+            if *xb == 0.0 {
+                return Err(VmError::DivideByZero);
+            }
+            let result = xa / xb;
+            if result.is_infinite() {
+                return Err(VmError::Infinity);
+            }
+            *xo = result;
+        }
+        Ok(())
+    }
+
+    pub fn rem_f32(a: &[f32], b: &[f32], out: &mut [f32]) -> Result<(), VmError> {
+        for ((xa, xb), xo) in a.iter().zip(b).zip(out) {
+            if *xb == 0.0 {
+                return Err(VmError::DivideByZero);
+            }
+            let result = xa % xb;
+            if result.is_infinite() {
+                return Err(VmError::Infinity);
+            }
+            *xo = result;
+        }
+        Ok(())
+    }
+
+    pub fn div_f64(a: &[f64], b: &[f64], out: &mut [f64]) -> Result<(), VmError> {
+        for ((xa, xb), xo) in a.iter().zip(b).zip(out) {
+            if *xb == 0.0 {
+                return Err(VmError::DivideByZero);
+            }
+            let result = xa / xb;
+            if result.is_infinite() {
+                return Err(VmError::Infinity);
+            }
+            *xo = result;
+        }
+        Ok(())
+    }
+
+    pub fn rem_f64(a: &[f64], b: &[f64], out: &mut [f64]) -> Result<(), VmError> {
+        for ((xa, xb), xo) in a.iter().zip(b).zip(out) {
+            if *xb == 0.0 {
+                return Err(VmError::DivideByZero);
+            }
+            let result = xa % xb;
+            if result.is_infinite() {
+                return Err(VmError::Infinity);
+            }
+            *xo = result;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! dispatch {
+    ($name:ident, $ty:ty) => {
+        pub fn $name(a: &[$ty], b: &[$ty], out: &mut [$ty]) -> Result<(), VmError> {
+            #[cfg(feature = "simd")]
+            {
+                vector::$name(a, b, out)
+            }
+            #[cfg(not(feature = "simd"))]
+            {
+                scalar::$name(a, b, out)
+            }
+        }
+    };
+}
+
+dispatch!(mul_i32, i32);
+dispatch!(div_i32, i32);
+dispatch!(rem_i32, i32);
+dispatch!(div_i64, i64);
+dispatch!(rem_i64, i64);
+dispatch!(div_f32, f32);
+dispatch!(rem_f32, f32);
+dispatch!(div_f64, f64);
+dispatch!(rem_f64, f64);
+
+/// No native 64x64-bit widening multiply exists in `core::simd` (no `i128`
+/// lane type) any more than it did in `std::arch`, so this always takes the
+/// scalar path regardless of the `simd` feature.
+pub fn mul_i64(a: &[i64], b: &[i64], out: &mut [i64]) -> Result<(), VmError> {
+    scalar::mul_i64(a, b, out)
+}
+
+#[cfg(all(test, feature = "simd"))]
+mod tests {
+    use super::*;
+
+    fn check_i32(vector_fn: fn(&[i32], &[i32], &mut [i32]) -> Result<(), VmError>, scalar_fn: fn(&[i32], &[i32], &mut [i32]) -> Result<(), VmError>, a: &[i32], b: &[i32]) {
+        let mut vector_out = vec![0i32; a.len()];
+        let mut scalar_out = vec![0i32; a.len()];
+        let vector_result = vector_fn(a, b, &mut vector_out);
+        let scalar_result = scalar_fn(a, b, &mut scalar_out);
+        assert_eq!(vector_result.is_ok(), scalar_result.is_ok());
+        if vector_result.is_ok() {
+            assert_eq!(vector_out, scalar_out);
+        }
+    }
+
+    #[test]
+    fn mul_i32_matches_scalar_on_width_4_and_remainder() {
+        check_i32(vector::mul_i32, scalar::mul_i32, &[3, -7, i32::MAX, 5], &[2, 4, 2, -1]);
+        check_i32(vector::mul_i32, scalar::mul_i32, &[3, -7, 9], &[2, 4, -1]);
+    }
+
+    #[test]
+    fn mul_i32_overflow_agrees_with_scalar() {
+        check_i32(vector::mul_i32, scalar::mul_i32, &[i32::MAX, 1, 1, 1], &[2, 1, 1, 1]);
+    }
+
+    #[test]
+    fn div_i32_divide_by_zero_agrees_with_scalar() {
+        check_i32(vector::div_i32, scalar::div_i32, &[10, 1, 1, 1], &[0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn div_i32_min_by_neg_one_overflow_agrees_with_scalar() {
+        check_i32(vector::div_i32, scalar::div_i32, &[i32::MIN, 1, 1, 1], &[-1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn rem_i32_matches_scalar() {
+        check_i32(vector::rem_i32, scalar::rem_i32, &[10, -7, 9, 5], &[3, 4, 2, -1]);
+    }
+
+    #[test]
+    fn div_f32_matches_scalar_including_infinity() {
+        let a = [1.0f32, 2.0, f32::MAX, 4.0];
+        let b = [2.0f32, 4.0, 0.5, 2.0];
+        let mut vector_out = [0f32; 4];
+        let mut scalar_out = [0f32; 4];
+        let vector_result = vector::div_f32(&a, &b, &mut vector_out);
+        let scalar_result = scalar::div_f32(&a, &b, &mut scalar_out);
+        assert_eq!(vector_result.is_err(), scalar_result.is_err());
+        assert_eq!(vector_result, Err(VmError::Infinity));
+    }
+}
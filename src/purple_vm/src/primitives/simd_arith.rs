@@ -0,0 +1,299 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! SIMD-accelerated element-wise `Add`/`Sub` for `VmValue`'s integer array
+//! variants, only reachable when the crate is built with the `simd` feature
+//! (`VmValue`'s scalar `.iter().zip()` loop is always correct and remains the
+//! fallback for every lane width and every target). `Mul`/`Div`/`Rem` moved
+//! to `portable_simd` (`core::simd`'s lane-comparison API is a better fit for
+//! their overflow/divide-by-zero masking than hand-rolled intrinsics).
+//!
+//! Each function here operates on whole slices rather than a single array
+//! width, so the same vectorized loop serves every lane count `value.rs`
+//! declares (2 through 256): it processes as many full hardware vectors as
+//! fit, then finishes the remainder (0-7 elements on x86_64 AVX2, 0-3 on
+//! NEON) with the identical scalar checked op.
+//!
+//! Overflow must be detected, not silently wrapped, so every vectorized add
+//! and sub additionally computes the classic branch-free signed-overflow
+//! mask (`(a ^ sum) & (b ^ sum)` for add, `(a ^ b) & (a ^ diff)` for sub; it
+//! is negative in a lane iff that lane's unchecked add/sub overflowed) and
+//! bails out to `VmError::Overflow` if any lane's mask is set.
+//!
+//! Floats are intentionally left out: IEEE-754 add/sub on SSE2/NEON are
+//! already bit-identical to the scalar native path `VmValue` uses when the
+//! `softfloat` feature is off, and when `softfloat` is on determinism is the
+//! entire point, so neither case benefits from a second hardware code path.
+
+use VmError;
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod x86_64_impl {
+    use super::VmError;
+    use std::arch::x86_64::*;
+
+    pub fn add_i32(a: &[i32], b: &[i32], out: &mut [i32]) -> Result<(), VmError> {
+        let mut i = 0;
+
+        unsafe {
+            while i + 8 <= a.len() {
+                let va = _mm256_loadu_si256(a.as_ptr().add(i) as *const __m256i);
+                let vb = _mm256_loadu_si256(b.as_ptr().add(i) as *const __m256i);
+                let sum = _mm256_add_epi32(va, vb);
+
+                let a_xor_sum = _mm256_xor_si256(va, sum);
+                let b_xor_sum = _mm256_xor_si256(vb, sum);
+                let overflowed = _mm256_and_si256(a_xor_sum, b_xor_sum);
+
+                if _mm256_movemask_ps(_mm256_castsi256_ps(overflowed)) != 0 {
+                    return Err(VmError::Overflow);
+                }
+
+                _mm256_storeu_si256(out.as_mut_ptr().add(i) as *mut __m256i, sum);
+                i += 8;
+            }
+        }
+
+        for j in i..a.len() {
+            out[j] = a[j].checked_add(b[j]).ok_or(VmError::Overflow)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn sub_i32(a: &[i32], b: &[i32], out: &mut [i32]) -> Result<(), VmError> {
+        let mut i = 0;
+
+        unsafe {
+            while i + 8 <= a.len() {
+                let va = _mm256_loadu_si256(a.as_ptr().add(i) as *const __m256i);
+                let vb = _mm256_loadu_si256(b.as_ptr().add(i) as *const __m256i);
+                let diff = _mm256_sub_epi32(va, vb);
+
+                let a_xor_b = _mm256_xor_si256(va, vb);
+                let a_xor_diff = _mm256_xor_si256(va, diff);
+                let overflowed = _mm256_and_si256(a_xor_b, a_xor_diff);
+
+                if _mm256_movemask_ps(_mm256_castsi256_ps(overflowed)) != 0 {
+                    return Err(VmError::Overflow);
+                }
+
+                _mm256_storeu_si256(out.as_mut_ptr().add(i) as *mut __m256i, diff);
+                i += 8;
+            }
+        }
+
+        for j in i..a.len() {
+            out[j] = a[j].checked_sub(b[j]).ok_or(VmError::Overflow)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn add_i64(a: &[i64], b: &[i64], out: &mut [i64]) -> Result<(), VmError> {
+        let mut i = 0;
+
+        unsafe {
+            while i + 4 <= a.len() {
+                let va = _mm256_loadu_si256(a.as_ptr().add(i) as *const __m256i);
+                let vb = _mm256_loadu_si256(b.as_ptr().add(i) as *const __m256i);
+                let sum = _mm256_add_epi64(va, vb);
+
+                let a_xor_sum = _mm256_xor_si256(va, sum);
+                let b_xor_sum = _mm256_xor_si256(vb, sum);
+                let overflowed = _mm256_and_si256(a_xor_sum, b_xor_sum);
+
+                if _mm256_movemask_pd(_mm256_castsi256_pd(overflowed)) != 0 {
+                    return Err(VmError::Overflow);
+                }
+
+                _mm256_storeu_si256(out.as_mut_ptr().add(i) as *mut __m256i, sum);
+                i += 4;
+            }
+        }
+
+        for j in i..a.len() {
+            out[j] = a[j].checked_add(b[j]).ok_or(VmError::Overflow)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn sub_i64(a: &[i64], b: &[i64], out: &mut [i64]) -> Result<(), VmError> {
+        let mut i = 0;
+
+        unsafe {
+            while i + 4 <= a.len() {
+                let va = _mm256_loadu_si256(a.as_ptr().add(i) as *const __m256i);
+                let vb = _mm256_loadu_si256(b.as_ptr().add(i) as *const __m256i);
+                let diff = _mm256_sub_epi64(va, vb);
+
+                let a_xor_b = _mm256_xor_si256(va, vb);
+                let a_xor_diff = _mm256_xor_si256(va, diff);
+                let overflowed = _mm256_and_si256(a_xor_b, a_xor_diff);
+
+                if _mm256_movemask_pd(_mm256_castsi256_pd(overflowed)) != 0 {
+                    return Err(VmError::Overflow);
+                }
+
+                _mm256_storeu_si256(out.as_mut_ptr().add(i) as *mut __m256i, diff);
+                i += 4;
+            }
+        }
+
+        for j in i..a.len() {
+            out[j] = a[j].checked_sub(b[j]).ok_or(VmError::Overflow)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+mod aarch64_impl {
+    use super::VmError;
+    use std::arch::aarch64::*;
+
+    pub fn add_i32(a: &[i32], b: &[i32], out: &mut [i32]) -> Result<(), VmError> {
+        let mut i = 0;
+
+        unsafe {
+            while i + 4 <= a.len() {
+                let va = vld1q_s32(a.as_ptr().add(i));
+                let vb = vld1q_s32(b.as_ptr().add(i));
+                let sum = vaddq_s32(va, vb);
+
+                let a_xor_sum = veorq_s32(va, sum);
+                let b_xor_sum = veorq_s32(vb, sum);
+                let overflowed = vandq_s32(a_xor_sum, b_xor_sum);
+
+                if vminvq_s32(overflowed) < 0 {
+                    return Err(VmError::Overflow);
+                }
+
+                vst1q_s32(out.as_mut_ptr().add(i), sum);
+                i += 4;
+            }
+        }
+
+        for j in i..a.len() {
+            out[j] = a[j].checked_add(b[j]).ok_or(VmError::Overflow)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn sub_i32(a: &[i32], b: &[i32], out: &mut [i32]) -> Result<(), VmError> {
+        let mut i = 0;
+
+        unsafe {
+            while i + 4 <= a.len() {
+                let va = vld1q_s32(a.as_ptr().add(i));
+                let vb = vld1q_s32(b.as_ptr().add(i));
+                let diff = vsubq_s32(va, vb);
+
+                let a_xor_b = veorq_s32(va, vb);
+                let a_xor_diff = veorq_s32(va, diff);
+                let overflowed = vandq_s32(a_xor_b, a_xor_diff);
+
+                if vminvq_s32(overflowed) < 0 {
+                    return Err(VmError::Overflow);
+                }
+
+                vst1q_s32(out.as_mut_ptr().add(i), diff);
+                i += 4;
+            }
+        }
+
+        for j in i..a.len() {
+            out[j] = a[j].checked_sub(b[j]).ok_or(VmError::Overflow)?;
+        }
+
+        Ok(())
+    }
+
+    // NEON has no native 64-bit-wide integer add/sub with an overflow flag,
+    // so `add_i64`/`sub_i64` fall back to the scalar loop on aarch64.
+    pub fn add_i64(a: &[i64], b: &[i64], out: &mut [i64]) -> Result<(), VmError> {
+        super::scalar::add_i64(a, b, out)
+    }
+
+    pub fn sub_i64(a: &[i64], b: &[i64], out: &mut [i64]) -> Result<(), VmError> {
+        super::scalar::sub_i64(a, b, out)
+    }
+}
+
+mod scalar {
+    use super::VmError;
+
+    pub fn add_i32(a: &[i32], b: &[i32], out: &mut [i32]) -> Result<(), VmError> {
+        for ((r, x), y) in out.iter_mut().zip(a.iter()).zip(b.iter()) {
+            *r = x.checked_add(*y).ok_or(VmError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn sub_i32(a: &[i32], b: &[i32], out: &mut [i32]) -> Result<(), VmError> {
+        for ((r, x), y) in out.iter_mut().zip(a.iter()).zip(b.iter()) {
+            *r = x.checked_sub(*y).ok_or(VmError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn add_i64(a: &[i64], b: &[i64], out: &mut [i64]) -> Result<(), VmError> {
+        for ((r, x), y) in out.iter_mut().zip(a.iter()).zip(b.iter()) {
+            *r = x.checked_add(*y).ok_or(VmError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn sub_i64(a: &[i64], b: &[i64], out: &mut [i64]) -> Result<(), VmError> {
+        for ((r, x), y) in out.iter_mut().zip(a.iter()).zip(b.iter()) {
+            *r = x.checked_sub(*y).ok_or(VmError::Overflow)?;
+        }
+        Ok(())
+    }
+
+}
+
+macro_rules! dispatch {
+    ($name:ident, $ty:ty) => {
+        pub fn $name(a: &[$ty], b: &[$ty], out: &mut [$ty]) -> Result<(), VmError> {
+            #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+            {
+                x86_64_impl::$name(a, b, out)
+            }
+            #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+            {
+                aarch64_impl::$name(a, b, out)
+            }
+            #[cfg(not(all(
+                feature = "simd",
+                any(target_arch = "x86_64", target_arch = "aarch64")
+            )))]
+            {
+                scalar::$name(a, b, out)
+            }
+        }
+    };
+}
+
+dispatch!(add_i32, i32);
+dispatch!(sub_i32, i32);
+dispatch!(add_i64, i64);
+dispatch!(sub_i64, i64);
@@ -0,0 +1,522 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Deterministic, bit-reproducible software floating point for `VmValue`'s
+//! `F32`/`F64` arithmetic.
+//!
+//! Native hardware float ops can differ across CPUs and compiler flags in how
+//! they round fused operations and handle subnormals. Since `VmValue`
+//! arithmetic feeds directly into consensus state, every validator must
+//! compute the exact same result bits regardless of target. These functions
+//! decompose operands into sign/exponent/mantissa, carry out the operation on
+//! integer mantissas with a guard/round/sticky bit, and round to-nearest-
+//! ties-to-even, matching the IEEE-754 default rounding mode bit-for-bit for
+//! normal results. A result too small for a normal number is flushed to a
+//! signed zero instead of being rounded to a subnormal.
+//!
+//! Only reachable when the crate is built with the `softfloat` feature;
+//! otherwise `VmValue` falls back to native hardware float ops.
+
+/// `f32` split into sign/unbiased-exponent/24-bit-significand (implicit
+/// leading bit folded in for normals, subnormals shifted up to the same
+/// form, zero left as a zero significand).
+struct F32Parts {
+    sign: bool,
+    exp: i32,
+    mant: u32,
+}
+
+impl F32Parts {
+    fn decompose(val: f32) -> F32Parts {
+        let bits = val.to_bits();
+        let sign = (bits >> 31) & 1 == 1;
+        let raw_exp = ((bits >> 23) & 0xff) as i32;
+        let raw_mant = bits & 0x007f_ffff;
+
+        if raw_exp == 0 {
+            // Zero or subnormal: no implicit leading bit. A subnormal is
+            // shifted up until bit 23 is set, like a normal significand.
+            let shift = if raw_mant == 0 { 0 } else { raw_mant.leading_zeros() as i32 - 8 };
+            F32Parts {
+                sign,
+                exp: -126 - shift,
+                mant: raw_mant << shift,
+            }
+        } else {
+            F32Parts {
+                sign,
+                exp: raw_exp - 127,
+                mant: raw_mant | 0x0080_0000,
+            }
+        }
+    }
+
+    /// Rounds a 24-bit-plus-guard/round/sticky significand to nearest-even
+    /// and reassembles the final `f32`, handling the carry-out-of-rounding
+    /// renormalization case.
+    fn round_and_pack(sign: bool, mut exp: i32, mant: u64, grs: u64) -> f32 {
+        // `mant` holds the 24-bit significand (bit 23 is the implicit one);
+        // `grs` holds guard/round/sticky packed as the low 3 bits of a
+        // conceptual 27-bit value: (mant << 3) | grs.
+        let round_up = {
+            let guard = (grs >> 2) & 1;
+            let round = (grs >> 1) & 1;
+            let sticky = grs & 1;
+
+            guard == 1 && (round == 1 || sticky == 1 || mant & 1 == 1)
+        };
+
+        let mut mant = mant;
+        if round_up {
+            mant += 1;
+        }
+
+        if mant & 0x0100_0000 != 0 {
+            // Rounding carried out of the top bit; renormalize.
+            mant >>= 1;
+            exp += 1;
+        }
+
+        if mant == 0 {
+            return if sign { -0.0 } else { 0.0 };
+        }
+
+        if exp >= 128 {
+            // Overflow to infinity; caller gates this via `check_f32_infinite`.
+            let bits = ((sign as u32) << 31) | (0xffu32 << 23);
+            return f32::from_bits(bits);
+        }
+
+        if exp <= -127 {
+            // Underflows below what a normal can hold; flush to zero rather
+            // than attempt subnormal rounding.
+            return if sign { -0.0 } else { 0.0 };
+        }
+
+        let biased_exp = (exp + 127) as u32;
+        let stored_mant = (mant as u32) & 0x007f_ffff;
+        let bits = ((sign as u32) << 31) | (biased_exp << 23) | stored_mant;
+
+        f32::from_bits(bits)
+    }
+}
+
+/// `f64` analogue of `F32Parts`, with a 53-bit significand.
+struct F64Parts {
+    sign: bool,
+    exp: i32,
+    mant: u64,
+}
+
+impl F64Parts {
+    fn decompose(val: f64) -> F64Parts {
+        let bits = val.to_bits();
+        let sign = (bits >> 63) & 1 == 1;
+        let raw_exp = ((bits >> 52) & 0x7ff) as i32;
+        let raw_mant = bits & 0x000f_ffff_ffff_ffff;
+
+        if raw_exp == 0 {
+            let shift = if raw_mant == 0 { 0 } else { raw_mant.leading_zeros() as i32 - 11 };
+            F64Parts {
+                sign,
+                exp: -1022 - shift,
+                mant: raw_mant << shift,
+            }
+        } else {
+            F64Parts {
+                sign,
+                exp: raw_exp - 1023,
+                mant: raw_mant | 0x0010_0000_0000_0000,
+            }
+        }
+    }
+
+    fn round_and_pack(sign: bool, mut exp: i32, mant: u128, grs: u128) -> f64 {
+        let round_up = {
+            let guard = (grs >> 2) & 1;
+            let round = (grs >> 1) & 1;
+            let sticky = grs & 1;
+
+            guard == 1 && (round == 1 || sticky == 1 || mant & 1 == 1)
+        };
+
+        let mut mant = mant;
+        if round_up {
+            mant += 1;
+        }
+
+        if mant & 0x0020_0000_0000_0000 != 0 {
+            mant >>= 1;
+            exp += 1;
+        }
+
+        if mant == 0 {
+            return if sign { -0.0 } else { 0.0 };
+        }
+
+        if exp >= 1024 {
+            let bits = ((sign as u64) << 63) | (0x7ffu64 << 52);
+            return f64::from_bits(bits);
+        }
+
+        if exp <= -1023 {
+            return if sign { -0.0 } else { 0.0 };
+        }
+
+        let biased_exp = (exp + 1023) as u64;
+        let stored_mant = (mant as u64) & 0x000f_ffff_ffff_ffff;
+        let bits = ((sign as u64) << 63) | (biased_exp << 52) | stored_mant;
+
+        f64::from_bits(bits)
+    }
+}
+
+/// Aligns `b`'s mantissa to `a`'s exponent, folding any shifted-out bits into
+/// a sticky bit so no precision is silently dropped before add/sub.
+fn align32(a: &F32Parts, b: &F32Parts) -> (u64, u64, i32) {
+    let shift = a.exp - b.exp;
+    let a_mant = (a.mant as u64) << 3;
+
+    if shift >= 27 {
+        (a_mant, if b.mant == 0 { 0 } else { 1 }, a.exp)
+    } else {
+        let wide = (b.mant as u64) << 3;
+        let shifted = wide >> shift;
+        let sticky = if wide & ((1u64 << shift) - 1) != 0 { 1 } else { 0 };
+        (a_mant, shifted | sticky, a.exp)
+    }
+}
+
+fn align64(a: &F64Parts, b: &F64Parts) -> (u128, u128, i32) {
+    let shift = a.exp - b.exp;
+    let a_mant = (a.mant as u128) << 3;
+
+    if shift >= 56 {
+        (a_mant, if b.mant == 0 { 0 } else { 1 }, a.exp)
+    } else {
+        let wide = (b.mant as u128) << 3;
+        let shifted = wide >> shift;
+        let sticky = if wide & ((1u128 << shift) - 1) != 0 { 1 } else { 0 };
+        (a_mant, shifted | sticky, a.exp)
+    }
+}
+
+pub fn add_f32(val1: f32, val2: f32) -> f32 {
+    let a = F32Parts::decompose(val1);
+    let b = F32Parts::decompose(val2);
+
+    if a.sign == b.sign {
+        let (wide_a, wide_b, exp) = if a.exp >= b.exp {
+            align32(&a, &b)
+        } else {
+            align32(&b, &a)
+        };
+
+        let sum = wide_a + wide_b;
+        let (mant, exp) = if sum & (1 << 27) != 0 {
+            ((sum >> 1) | (sum & 1), exp + 1)
+        } else {
+            (sum, exp)
+        };
+
+        F32Parts::round_and_pack(a.sign, exp, mant >> 3, mant & 0b111)
+    } else {
+        sub_f32(val1, f32::from_bits(val2.to_bits() ^ 0x8000_0000))
+    }
+}
+
+pub fn sub_f32(val1: f32, val2: f32) -> f32 {
+    let a = F32Parts::decompose(val1);
+    let b = F32Parts::decompose(val2);
+
+    if a.sign != b.sign {
+        return add_f32(val1, f32::from_bits(val2.to_bits() ^ 0x8000_0000));
+    }
+
+    let (bigger, smaller, result_sign, exp) = if a.exp > b.exp
+        || (a.exp == b.exp && a.mant >= b.mant)
+    {
+        let (wa, wb, exp) = align32(&a, &b);
+        (wa, wb, a.sign, exp)
+    } else {
+        let (wb, wa, exp) = align32(&b, &a);
+        (wb, wa, !a.sign, exp)
+    };
+
+    let diff = bigger - smaller;
+
+    if diff == 0 {
+        return 0.0;
+    }
+
+    // Normalize: shift left until the implicit bit (position 26, given the
+    // 3 guard/round/sticky bits packed below it) is set.
+    let shift = (diff.leading_zeros() as i32) - (64 - 27);
+    let (mant, exp) = if shift > 0 {
+        (diff << shift, exp - shift)
+    } else {
+        (diff, exp)
+    };
+
+    F32Parts::round_and_pack(result_sign, exp, mant >> 3, mant & 0b111)
+}
+
+pub fn mul_f32(val1: f32, val2: f32) -> f32 {
+    let a = F32Parts::decompose(val1);
+    let b = F32Parts::decompose(val2);
+
+    if a.mant == 0 || b.mant == 0 {
+        return if a.sign != b.sign { -0.0 } else { 0.0 };
+    }
+
+    let sign = a.sign != b.sign;
+    let exp = a.exp + b.exp;
+
+    // 24x24-bit multiply fits comfortably in a u64; the product occupies up
+    // to 48 bits with the implicit-bit product at bit 46 or 47.
+    let product = (a.mant as u64) * (b.mant as u64);
+
+    let (mant, exp) = if product & (1 << 47) != 0 {
+        (product, exp + 1)
+    } else {
+        (product << 1, exp)
+    };
+
+    // Top 24 bits become the rounded significand; everything below folds
+    // into guard/round/sticky.
+    let top = mant >> 24;
+    let rest = mant & 0x00ff_ffff;
+    let grs = ((rest >> 21) & 0b110) | if rest & 0x003f_ffff != 0 { 1 } else { 0 };
+
+    F32Parts::round_and_pack(sign, exp, top, grs)
+}
+
+pub fn div_f32(val1: f32, val2: f32) -> f32 {
+    let a = F32Parts::decompose(val1);
+    let b = F32Parts::decompose(val2);
+
+    if a.mant == 0 {
+        return if a.sign != b.sign { -0.0 } else { 0.0 };
+    }
+
+    let sign = a.sign != b.sign;
+    let exp = a.exp - b.exp;
+
+    // Non-restoring shift-subtract division on the 24-bit significands,
+    // carried out 26 times so the quotient plus a few extra low bits leave
+    // enough information for a correct guard/round/sticky decision.
+    let mut remainder = a.mant as u64;
+    let divisor = b.mant as u64;
+    let mut quotient: u64 = 0;
+
+    for _ in 0..26 {
+        quotient <<= 1;
+        remainder <<= 1;
+
+        if remainder >= (divisor << 1) {
+            remainder -= divisor << 1;
+            quotient |= 1;
+        }
+    }
+
+    let sticky = if remainder != 0 { 1 } else { 0 };
+
+    let (mant, exp) = if quotient & (1 << 25) != 0 {
+        (quotient, exp)
+    } else {
+        (quotient << 1, exp - 1)
+    };
+
+    let top = mant >> 2;
+    let grs = (mant & 0b11) << 1 | sticky;
+
+    F32Parts::round_and_pack(sign, exp, top, grs)
+}
+
+pub fn add_f64(val1: f64, val2: f64) -> f64 {
+    let a = F64Parts::decompose(val1);
+    let b = F64Parts::decompose(val2);
+
+    if a.sign == b.sign {
+        let (wide_a, wide_b, exp) = if a.exp >= b.exp {
+            align64(&a, &b)
+        } else {
+            align64(&b, &a)
+        };
+
+        let sum = wide_a + wide_b;
+        let (mant, exp) = if sum & (1 << 56) != 0 {
+            ((sum >> 1) | (sum & 1), exp + 1)
+        } else {
+            (sum, exp)
+        };
+
+        F64Parts::round_and_pack(a.sign, exp, mant >> 3, mant & 0b111)
+    } else {
+        sub_f64(val1, f64::from_bits(val2.to_bits() ^ 0x8000_0000_0000_0000))
+    }
+}
+
+pub fn sub_f64(val1: f64, val2: f64) -> f64 {
+    let a = F64Parts::decompose(val1);
+    let b = F64Parts::decompose(val2);
+
+    if a.sign != b.sign {
+        return add_f64(val1, f64::from_bits(val2.to_bits() ^ 0x8000_0000_0000_0000));
+    }
+
+    let (bigger, smaller, result_sign, exp) = if a.exp > b.exp
+        || (a.exp == b.exp && a.mant >= b.mant)
+    {
+        let (wa, wb, exp) = align64(&a, &b);
+        (wa, wb, a.sign, exp)
+    } else {
+        let (wb, wa, exp) = align64(&b, &a);
+        (wb, wa, !a.sign, exp)
+    };
+
+    let diff = bigger - smaller;
+
+    if diff == 0 {
+        return 0.0;
+    }
+
+    let shift = (diff.leading_zeros() as i32) - (128 - 56);
+    let (mant, exp) = if shift > 0 {
+        (diff << shift, exp - shift)
+    } else {
+        (diff, exp)
+    };
+
+    F64Parts::round_and_pack(result_sign, exp, mant >> 3, mant & 0b111)
+}
+
+pub fn mul_f64(val1: f64, val2: f64) -> f64 {
+    let a = F64Parts::decompose(val1);
+    let b = F64Parts::decompose(val2);
+
+    if a.mant == 0 || b.mant == 0 {
+        return if a.sign != b.sign { -0.0 } else { 0.0 };
+    }
+
+    let sign = a.sign != b.sign;
+    let exp = a.exp + b.exp;
+
+    let product = (a.mant as u128) * (b.mant as u128);
+
+    let (mant, exp) = if product & (1 << 105) != 0 {
+        (product, exp + 1)
+    } else {
+        (product << 1, exp)
+    };
+
+    let top = mant >> 53;
+    let rest = mant & 0x001f_ffff_ffff_ffff;
+    let grs = ((rest >> 50) & 0b110) | if rest & 0x0007_ffff_ffff_ffff != 0 { 1 } else { 0 };
+
+    F64Parts::round_and_pack(sign, exp, top, grs)
+}
+
+pub fn div_f64(val1: f64, val2: f64) -> f64 {
+    let a = F64Parts::decompose(val1);
+    let b = F64Parts::decompose(val2);
+
+    if a.mant == 0 {
+        return if a.sign != b.sign { -0.0 } else { 0.0 };
+    }
+
+    let sign = a.sign != b.sign;
+    let exp = a.exp - b.exp;
+
+    let mut remainder = a.mant as u128;
+    let divisor = b.mant as u128;
+    let mut quotient: u128 = 0;
+
+    for _ in 0..55 {
+        quotient <<= 1;
+        remainder <<= 1;
+
+        if remainder >= (divisor << 1) {
+            remainder -= divisor << 1;
+            quotient |= 1;
+        }
+    }
+
+    let sticky = if remainder != 0 { 1 } else { 0 };
+
+    let (mant, exp) = if quotient & (1 << 54) != 0 {
+        (quotient, exp)
+    } else {
+        (quotient << 1, exp - 1)
+    };
+
+    let top = mant >> 2;
+    let grs = (mant & 0b11) << 1 | sticky;
+
+    F64Parts::round_and_pack(sign, exp, top, grs)
+}
+
+pub fn rem_f32(val1: f32, val2: f32) -> f32 {
+    // Remainder by truncated division: val1 - trunc(val1 / val2) * val2,
+    // computed entirely through the soft ops above so it stays deterministic.
+    let quotient = div_f32(val1, val2).trunc();
+    sub_f32(val1, mul_f32(quotient, val2))
+}
+
+pub fn rem_f64(val1: f64, val2: f64) -> f64 {
+    let quotient = div_f64(val1, val2).trunc();
+    sub_f64(val1, mul_f64(quotient, val2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_matches_native_addition_on_well_defined_inputs() {
+        let cases: &[(f32, f32)] = &[
+            (1.5, 2.25),
+            (100.0, -42.5),
+            (0.1, 0.2),
+            (1e10, 1.0),
+            (-3.0, 3.0),
+        ];
+
+        for &(a, b) in cases {
+            assert_eq!(add_f32(a, b).to_bits(), (a + b).to_bits());
+        }
+    }
+
+    #[test]
+    fn it_matches_native_multiplication_on_well_defined_inputs() {
+        let cases: &[(f32, f32)] = &[(1.5, 2.0), (3.0, -4.0), (0.5, 0.5), (123.456, 7.89)];
+
+        for &(a, b) in cases {
+            assert_eq!(mul_f32(a, b).to_bits(), (a * b).to_bits());
+        }
+    }
+
+    #[test]
+    fn it_matches_native_division_on_well_defined_inputs() {
+        let cases: &[(f64, f64)] = &[(10.0, 4.0), (1.0, 3.0), (-9.0, 2.0), (1234.5, 67.8)];
+
+        for &(a, b) in cases {
+            assert_eq!(div_f64(a, b).to_bits(), (a / b).to_bits());
+        }
+    }
+}
@@ -0,0 +1,226 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Deterministic transcendental and rounding math for `VmValue`'s `F32`/`F64`
+//! variants, implemented without libm so every validator produces bit-
+//! identical results regardless of the host's math library.
+
+const FRAC_PI_2: f64 = std::f64::consts::FRAC_PI_2;
+
+/// Newton-Raphson square root seeded by the classic "fast inverse square
+/// root" bit-hack initial guess, run for a fixed number of iterations so the
+/// result depends only on the input bits, never on convergence behavior.
+///
+/// The bit-hack guess approximates `1/sqrt(val)`, not `sqrt(val)`, so the
+/// Newton iteration below refines the *inverse* square root
+/// (`x = x * (1.5 - 0.5*val*x*x)`, the iteration that actually converges
+/// from that seed for every magnitude of `val`) and the final result is
+/// recovered as `val * x`.
+pub fn sqrt_f64(val: f64) -> f64 {
+    if val == 0.0 {
+        return val;
+    }
+
+    let i = val.to_bits();
+    let guess_bits = 0x5fe6_eb50_c7b5_37a9u64.wrapping_sub(i >> 1);
+    let mut x = f64::from_bits(guess_bits);
+
+    for _ in 0..10 {
+        x = x * (1.5 - 0.5 * val * x * x);
+    }
+
+    val * x
+}
+
+pub fn sqrt_f32(val: f32) -> f32 {
+    if val == 0.0 {
+        return val;
+    }
+
+    let i = val.to_bits();
+    let guess_bits = 0x5f37_59dfu32.wrapping_sub(i >> 1);
+    let mut x = f32::from_bits(guess_bits);
+
+    for _ in 0..6 {
+        x = x * (1.5 - 0.5 * val * x * x);
+    }
+
+    val * x
+}
+
+/// Reduces `val` modulo pi/2, returning the reduced angle and the quadrant
+/// (0..=3) it fell in, so `sin`/`cos` can be derived from the `sin_poly` and
+/// `cos_poly` polynomials valid on `[-pi/4, pi/4]`.
+fn reduce_quadrant(val: f64) -> (f64, u32) {
+    let k = (val / FRAC_PI_2).round();
+    let reduced = val - k * FRAC_PI_2;
+    let quadrant = ((k as i64).rem_euclid(4)) as u32;
+
+    (reduced, quadrant)
+}
+
+/// 9th-degree polynomial approximation of `sin(x)` on `[-pi/4, pi/4]`, using
+/// the plain Taylor coefficients `±1/n!`; they are not minimax-fitted to the
+/// reduced range (accurate enough for VM contract arithmetic; this is not a
+/// claim of last-bit correctness).
+fn sin_poly(x: f64) -> f64 {
+    let x2 = x * x;
+    x * (1.0
+        + x2 * (-1.0 / 6.0
+            + x2 * (1.0 / 120.0 + x2 * (-1.0 / 5040.0 + x2 * (1.0 / 362_880.0)))))
+}
+
+fn cos_poly(x: f64) -> f64 {
+    let x2 = x * x;
+    1.0 + x2 * (-0.5 + x2 * (1.0 / 24.0 + x2 * (-1.0 / 720.0 + x2 * (1.0 / 40_320.0))))
+}
+
+pub fn sin_f64(val: f64) -> f64 {
+    let (x, quadrant) = reduce_quadrant(val);
+
+    match quadrant {
+        0 => sin_poly(x),
+        1 => cos_poly(x),
+        2 => -sin_poly(x),
+        _ => -cos_poly(x),
+    }
+}
+
+pub fn cos_f64(val: f64) -> f64 {
+    let (x, quadrant) = reduce_quadrant(val);
+
+    match quadrant {
+        0 => cos_poly(x),
+        1 => -sin_poly(x),
+        2 => -cos_poly(x),
+        _ => sin_poly(x),
+    }
+}
+
+pub fn sin_f32(val: f32) -> f32 {
+    sin_f64(val as f64) as f32
+}
+
+pub fn cos_f32(val: f32) -> f32 {
+    cos_f64(val as f64) as f32
+}
+
+pub fn floor_f64(val: f64) -> f64 {
+    let bits = val.to_bits();
+    let sign = bits >> 63;
+    let exp = ((bits >> 52) & 0x7ff) as i32 - 1023;
+
+    if exp < 0 {
+        // |val| < 1.0
+        return if sign == 1 && val != 0.0 { -1.0 } else { 0.0 };
+    }
+
+    if exp >= 52 {
+        // No fractional bits to clear.
+        return val;
+    }
+
+    let frac_mask = (1u64 << (52 - exp)) - 1;
+    let truncated = f64::from_bits(bits & !frac_mask);
+
+    if sign == 1 && truncated != val {
+        truncated - 1.0
+    } else {
+        truncated
+    }
+}
+
+pub fn ceil_f64(val: f64) -> f64 {
+    -floor_f64(-val)
+}
+
+pub fn floor_f32(val: f32) -> f32 {
+    floor_f64(val as f64) as f32
+}
+
+pub fn ceil_f32(val: f32) -> f32 {
+    ceil_f64(val as f64) as f32
+}
+
+/// Scales `val` by `2^exp` by adjusting the exponent field directly.
+pub fn scalbn_f64(val: f64, exp: i32) -> f64 {
+    let bits = val.to_bits();
+    let biased_exp = ((bits >> 52) & 0x7ff) as i32;
+    let new_exp = (biased_exp + exp).clamp(0, 0x7ff) as u64;
+    let rest = bits & 0x800f_ffff_ffff_ffff;
+
+    f64::from_bits(rest | (new_exp << 52))
+}
+
+pub fn scalbn_f32(val: f32, exp: i32) -> f32 {
+    let bits = val.to_bits();
+    let biased_exp = ((bits >> 23) & 0xff) as i32;
+    let new_exp = (biased_exp + exp).clamp(0, 0xff) as u32;
+    let rest = bits & 0x807f_ffff;
+
+    f32::from_bits(rest | (new_exp << 23))
+}
+
+/// Copies the sign bit of `sign_src` onto `val`.
+pub fn copysign_f64(val: f64, sign_src: f64) -> f64 {
+    let magnitude = val.to_bits() & 0x7fff_ffff_ffff_ffff;
+    let sign = sign_src.to_bits() & 0x8000_0000_0000_0000;
+
+    f64::from_bits(magnitude | sign)
+}
+
+pub fn copysign_f32(val: f32, sign_src: f32) -> f32 {
+    let magnitude = val.to_bits() & 0x7fff_ffff;
+    let sign = sign_src.to_bits() & 0x8000_0000;
+
+    f32::from_bits(magnitude | sign)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqrt_is_close_to_native() {
+        for &val in &[2.0_f64, 9.0, 0.25, 1234.5678] {
+            assert!((sqrt_f64(val) - val.sqrt()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn sin_cos_are_close_to_native() {
+        for &val in &[0.0_f64, 0.5, 1.0, 2.0, -1.5, 10.0] {
+            assert!((sin_f64(val) - val.sin()).abs() < 1e-6);
+            assert!((cos_f64(val) - val.cos()).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn floor_ceil_match_native_on_fractional_inputs() {
+        for &val in &[1.5_f64, -1.5, 2.0, -2.0, 0.1, -0.1] {
+            assert_eq!(floor_f64(val), val.floor());
+            assert_eq!(ceil_f64(val), val.ceil());
+        }
+    }
+
+    #[test]
+    fn copysign_transfers_only_the_sign_bit() {
+        assert_eq!(copysign_f64(3.0, -1.0), -3.0);
+        assert_eq!(copysign_f64(-3.0, 1.0), 3.0);
+    }
+}
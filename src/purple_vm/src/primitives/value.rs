@@ -16,6 +16,15 @@
   along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
 */
 
+#[cfg(feature = "softfloat")]
+use super::softfloat;
+use super::fe25519;
+use super::fuel::FuelContext;
+use super::portable_simd;
+use super::simd_arith;
+use super::transcendental;
+use half::slice::{HalfBFloatSliceExt, HalfFloatSliceExt};
+use half::{bf16, f16};
 use std::cmp::{Ordering, PartialOrd};
 use std::fmt;
 use std::ops::{Add, Div, Mul, Rem, Sub};
@@ -26,8 +35,23 @@ use VmError;
 pub enum VmValue {
     I32(i32),
     I64(i64),
+    I128(i128),
+    U128(u128),
     F32(f32),
     F64(f64),
+
+    /// IEEE 754-2008 binary16 ("half precision"): 1 sign bit, 5 exponent
+    /// bits, 10 mantissa bits. See `to_f32`/`to_f16` for lossy conversion
+    /// to/from `F32`, and the `f16ArrayN` variants for the array form.
+    F16(f16),
+    /// "Brain float": the same exponent range as `f32` (8 bits) truncated to
+    /// a 7-bit mantissa, i.e. literally `f32`'s high 16 bits rounded to
+    /// nearest-even. See `to_f32`/`to_bf16` and the `bf16ArrayN` variants.
+    Bf16(bf16),
+
+    /// An element of GF(2^255 - 19), the curve25519/Ed25519 base field, as
+    /// five 51-bit limbs. See `primitives::fe25519` for the arithmetic.
+    Fe25519([u64; 5]),
     i32Array2([i32; 2]),
     i32Array4([i32; 4]),
     i32Array8([i32; 8]),
@@ -60,120 +84,2008 @@ pub enum VmValue {
     f64Array64([f64; 64]),
     f64Array128([f64; 128]),
     f64Array256([f64; 256]),
+    f16Array2([f16; 2]),
+    f16Array4([f16; 4]),
+    f16Array8([f16; 8]),
+    f16Array16([f16; 16]),
+    f16Array32([f16; 32]),
+    f16Array64([f16; 64]),
+    f16Array128([f16; 128]),
+    f16Array256([f16; 256]),
+    bf16Array2([bf16; 2]),
+    bf16Array4([bf16; 4]),
+    bf16Array8([bf16; 8]),
+    bf16Array16([bf16; 16]),
+    bf16Array32([bf16; 32]),
+    bf16Array64([bf16; 64]),
+    bf16Array128([bf16; 128]),
+    bf16Array256([bf16; 256]),
+}
+
+/// Overflow policy for `VmValue`'s `Mul`/`Div`/`Rem` opcodes to choose
+/// between, so a contract that wants the cheaper modular or clamped
+/// semantics real stack VMs (e.g. wasm's `i32.mul`) offer doesn't have to
+/// abort the whole transaction over an overflow it already expected.
+///
+/// Only the integer scalar variants (`I32`/`I64`/`I128`/`U128`) change
+/// behavior under `Wrapping`/`Saturating`: floats have no `Overflow` case to
+/// relax (`Infinity` is already the result IEEE-754 arithmetic produces),
+/// Fe25519 is already reduced modulo its field prime, and the array variants
+/// keep today's checked-only lane arithmetic regardless of mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithMode {
+    /// `VmValue`'s long-standing default: overflow and divide-by-zero return
+    /// a `VmError` (see `impl Mul`/`Div`/`Rem` below).
+    Checked,
+    /// Integer overflow wraps around modulo the type's width instead of
+    /// erroring; divide-by-zero still errors.
+    Wrapping,
+    /// Integer overflow clamps to the type's min/max instead of erroring;
+    /// divide-by-zero still errors.
+    Saturating,
+}
+
+/// The scalar element type underlying a `VmValue`, independent of how many
+/// lanes it has; see `VmTypeDesc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VmElementKind {
+    I32,
+    I64,
+    I128,
+    U128,
+    F32,
+    F64,
+    F16,
+    Bf16,
+    Fe25519,
+}
+
+/// A `VmValue`'s shape, independent of the particular value it currently
+/// holds: which element type its lanes are, how many lanes it has (`1` for
+/// every scalar variant, `2..=256` for the array families), and the value's
+/// total encoded width in bytes (see `byte_size`). Host code can compare a
+/// stack value's `describe()` against an expected signature before invoking
+/// a VM function, instead of string-matching `Debug` output or exhaustively
+/// matching all ~60 `VmValue` variants itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VmTypeDesc {
+    pub kind: VmElementKind,
+    pub lane_count: usize,
+    pub byte_size: usize,
+}
+
+impl fmt::Display for VmTypeDesc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.lane_count == 1 {
+            write!(f, "{:?}", self.kind)
+        } else {
+            write!(f, "{:?}x{}", self.kind, self.lane_count)
+        }
+    }
+}
+
+/// Foreground color for a `render`ed span. Kept to the handful of
+/// semantic categories `render` actually distinguishes rather than a full
+/// RGB palette, since this exists for stack/heap dump legibility, not
+/// general graphics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    /// No color annotation — used for every span under `RenderMode::Plain`.
+    Default,
+    /// Any scalar variant, including `Fe25519`.
+    Scalar,
+    /// `i32Array*`/`i64Array*`.
+    IntArray,
+    /// `f32Array*`/`f64Array*`/`f16Array*`/`bf16Array*`.
+    FloatArray,
+}
+
+/// One piece of rendered text plus the formatting it should carry,
+/// independent of whether the caller ends up wanting plain text, ANSI
+/// escapes, or a structured tree to style itself (e.g. a GUI debugger) —
+/// the same separation of content from formatting that chat/log message
+/// components use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyledSpan {
+    pub text: String,
+    pub color: Color,
+    pub bold: bool,
+    pub dim: bool,
+}
+
+/// `render`'s return type: a sequence of `StyledSpan`s (today, always
+/// exactly one — the formatted value itself — but kept as a `Vec` so a
+/// future `render` of e.g. a labeled stack slot can prepend a separately
+/// styled label span without changing the type).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyledText {
+    pub spans: Vec<StyledSpan>,
+}
+
+impl StyledText {
+    /// Concatenates every span's text, discarding formatting.
+    pub fn to_plain(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    /// Renders every span with ANSI SGR escape codes for its `color`/`bold`/
+    /// `dim` flags, for a terminal that understands them.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+
+        for span in &self.spans {
+            let mut codes: Vec<&'static str> = Vec::new();
+            if span.bold {
+                codes.push("1");
+            }
+            if span.dim {
+                codes.push("2");
+            }
+            match span.color {
+                Color::Default => {}
+                Color::Scalar => codes.push("33"),
+                Color::IntArray => codes.push("36"),
+                Color::FloatArray => codes.push("35"),
+            }
+
+            if codes.is_empty() {
+                out.push_str(&span.text);
+            } else {
+                out.push_str("\x1b[");
+                out.push_str(&codes.join(";"));
+                out.push('m');
+                out.push_str(&span.text);
+                out.push_str("\x1b[0m");
+            }
+        }
+
+        out
+    }
+}
+
+/// Which flavor `render` should produce, and how many array lanes to show
+/// before eliding the rest as `… +N more` (`render` always elides past this
+/// threshold regardless of mode — it exists so dumping e.g. a `f64Array256`
+/// doesn't flood the screen, not just to save color codes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderStyle {
+    pub mode: RenderMode,
+    pub max_elements: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    /// No color/weight annotations; `StyledText::to_plain()` is the only
+    /// meaningful way to flatten the result.
+    Plain,
+    /// Same spans, colored, meant to be flattened with `StyledText::to_ansi()`.
+    Ansi,
+    /// Same colored spans, left structured for a caller that applies its
+    /// own formatting instead of raw ANSI escapes.
+    Components,
+}
+
+impl RenderStyle {
+    /// Lanes shown before eliding, for `plain`/`ansi`/`components`'s default.
+    pub const DEFAULT_MAX_ELEMENTS: usize = 8;
+
+    pub fn plain() -> Self {
+        RenderStyle {
+            mode: RenderMode::Plain,
+            max_elements: Self::DEFAULT_MAX_ELEMENTS,
+        }
+    }
+
+    pub fn ansi() -> Self {
+        RenderStyle {
+            mode: RenderMode::Ansi,
+            max_elements: Self::DEFAULT_MAX_ELEMENTS,
+        }
+    }
+
+    pub fn components() -> Self {
+        RenderStyle {
+            mode: RenderMode::Components,
+            max_elements: Self::DEFAULT_MAX_ELEMENTS,
+        }
+    }
+
+    /// Overrides the elision threshold (see `RenderStyle`'s doc comment).
+    pub fn with_max_elements(mut self, max_elements: usize) -> Self {
+        self.max_elements = max_elements;
+        self
+    }
+}
+
+// The array variants above come in 8 lane counts (2/4/8/16/32/64/128/256)
+// crossed with 4 element types, and every arithmetic trait needs an
+// element-wise body for each of the 32 combinations. Hand-copying that arm
+// 32 times per trait (as byte_size/is_positive/PartialEq/Add/Sub used to)
+// is how the wider lanes silently ended up unimplemented. These macros
+// generate one arm per invocation so adding a lane count or an operator
+// means adding invocations, not drifting copies.
+//
+// Note lanes above 32 predate std's blanket trait impls for big arrays, so
+// `==` isn't available on them directly; `eq_iter_arm!` compares via
+// `.iter().eq()` instead, the same way `fmt::Debug` below already sidesteps
+// the limit by going through `.to_vec()`.
+macro_rules! byte_size_arm {
+    ($variant:ident, $elem_size:expr, $n:expr) => {
+        VmValue::$variant(_) => Ok($elem_size * $n)
+    };
+}
+
+macro_rules! lane_count_arm {
+    ($variant:ident, $n:expr) => {
+        VmValue::$variant(_) => $n
+    };
+}
+
+macro_rules! element_kind_arm {
+    ($variant:ident, $kind:expr) => {
+        VmValue::$variant(_) => $kind
+    };
+}
+
+// One macro covers every array family here (unlike `is_positive_*_arm!`'s
+// int/float/half split) since `render` only needs each lane's `Display`
+// output, and `i32`/`i64`/`f32`/`f64`/`f16`/`bf16` all implement `Display`.
+macro_rules! lane_strings_arm {
+    ($variant:ident) => {
+        VmValue::$variant(val) => val.iter().map(|v| format!("{}", v)).collect()
+    };
+}
+
+macro_rules! is_positive_int_arm {
+    ($variant:ident) => {
+        VmValue::$variant(val) => Ok(val.iter().all(|&v| v >= 0))
+    };
+}
+
+macro_rules! is_positive_float_arm {
+    ($variant:ident) => {
+        VmValue::$variant(val) => Ok(val.iter().all(|&v| v >= 0.0))
+    };
+}
+
+// `f16`/`bf16` aren't comparable to the bare literal `0.0` the way `f32`/
+// `f64` are, so they get their own arm rather than reusing
+// `is_positive_float_arm!`.
+macro_rules! is_positive_half_arm {
+    ($variant:ident, $zero:expr) => {
+        VmValue::$variant(val) => Ok(val.iter().all(|&v| v >= $zero))
+    };
+}
+
+macro_rules! eq_direct_arm {
+    ($variant:ident) => {
+        (VmValue::$variant(val1), VmValue::$variant(val2)) => val1 == val2
+    };
+}
+
+macro_rules! eq_iter_arm {
+    ($variant:ident) => {
+        (VmValue::$variant(val1), VmValue::$variant(val2)) => val1.iter().eq(val2.iter())
+    };
+}
+
+macro_rules! int_arith_arm {
+    ($variant:ident, $ty:ty, $n:expr, $simd_fn:ident) => {
+        (VmValue::$variant(val1), VmValue::$variant(val2)) => {
+            let mut result: [$ty; $n] = [0; $n];
+            simd_arith::$simd_fn(&val1, &val2, &mut result)?;
+            Ok(VmValue::$variant(result))
+        }
+    };
+}
+
+// `Mul`'s overflow check and `Div`/`Rem`'s divide-by-zero masking are both
+// naturally expressed with `core::simd`'s lane-comparison API, so (unlike
+// `add_i32`/`sub_i32` above, which stay on `simd_arith`'s arch-specific
+// intrinsics) these three route through `portable_simd` instead; see that
+// module for why.
+macro_rules! int_mul_arm {
+    ($variant:ident, $ty:ty, $n:expr, $simd_fn:ident) => {
+        (VmValue::$variant(val1), VmValue::$variant(val2)) => {
+            let mut result: [$ty; $n] = [0; $n];
+            portable_simd::$simd_fn(&val1, &val2, &mut result)?;
+            Ok(VmValue::$variant(result))
+        }
+    };
+}
+
+/// Widens a `f16`/`bf16` array to the `f32` array of the same width in a
+/// single pass via `half`'s vectorized `to_f32_slice`, rather than looping
+/// `to_f32()` per element.
+macro_rules! widen_to_f32_arm {
+    ($variant:ident, $out_variant:ident, $n:expr) => {
+        VmValue::$variant(val) => {
+            let mut result: [f32; $n] = [0.0; $n];
+            val.to_f32_slice(&mut result);
+            Ok(VmValue::$out_variant(result))
+        }
+    };
+}
+
+/// Narrows a `f32` array to `f16`/`bf16` in a single pass via `half`'s
+/// vectorized `from_f32_slice`, which saturates to `+-inf` on overflow and
+/// flushes subnormals, rather than truncating.
+macro_rules! narrow_from_f32_arm {
+    ($variant:ident, $out_variant:ident, $ty:ty, $zero:expr, $n:expr) => {
+        VmValue::$variant(val) => {
+            let mut result: [$ty; $n] = [$zero; $n];
+            result.from_f32_slice(&val);
+            Ok(VmValue::$out_variant(result))
+        }
+    };
+}
+
+/// Widens each `i32` lane to `i64` before multiplying, so the product can
+/// never overflow the wider lane regardless of the inputs; used by
+/// `mul_wide`'s array arms (see `Mul`'s `int_mul_arm!` for the checked,
+/// same-width equivalent).
+macro_rules! mul_wide_arm {
+    ($i32_variant:ident, $i64_variant:ident, $n:expr) => {
+        (VmValue::$i32_variant(val1), VmValue::$i32_variant(val2)) => {
+            let mut result: [i64; $n] = [0; $n];
+            for ((r, a), b) in result.iter_mut().zip(val1.iter()).zip(val2.iter()) {
+                *r = *a as i64 * *b as i64;
+            }
+            Ok(VmValue::$i64_variant(result))
+        }
+    };
+}
+
+macro_rules! int_divrem_arm {
+    ($variant:ident, $ty:ty, $n:expr, $simd_fn:ident) => {
+        (VmValue::$variant(val1), VmValue::$variant(val2)) => {
+            let mut result: [$ty; $n] = [0; $n];
+            portable_simd::$simd_fn(&val1, &val2, &mut result)?;
+            Ok(VmValue::$variant(result))
+        }
+    };
+}
+
+macro_rules! float_arith_arm {
+    ($variant:ident, $ty:ty, $n:expr, $helper:ident) => {
+        (VmValue::$variant(val1), VmValue::$variant(val2)) => {
+            let mut result: [$ty; $n] = [0.0; $n];
+
+            for ((r, a), b) in result.iter_mut().zip(val1.iter()).zip(val2.iter()) {
+                *r = VmValue::$helper(a, b).ok_or(VmError::Infinity)?;
+            }
+
+            Ok(VmValue::$variant(result))
+        }
+    };
+}
+
+macro_rules! float_divrem_arm {
+    ($variant:ident, $ty:ty, $n:expr, $simd_fn:ident) => {
+        (VmValue::$variant(val1), VmValue::$variant(val2)) => {
+            let mut result: [$ty; $n] = [0.0; $n];
+            portable_simd::$simd_fn(&val1, &val2, &mut result)?;
+            Ok(VmValue::$variant(result))
+        }
+    };
+}
+
+/// Collapses every `f32` NaN bit pattern to one canonical quiet NaN and
+/// negative zero to positive zero, so two logically-equal floats always
+/// produce byte-identical `to_canonical_bytes` output regardless of which
+/// hardware/compiler produced them (x86 and ARM disagree on which NaN
+/// payload a given operation leaves behind).
+fn canonical_f32(val: f32) -> f32 {
+    if val.is_nan() {
+        f32::from_bits(0x7fc0_0000)
+    } else if val == 0.0 {
+        0.0
+    } else {
+        val
+    }
+}
+
+/// `f64` counterpart of `canonical_f32`.
+fn canonical_f64(val: f64) -> f64 {
+    if val.is_nan() {
+        f64::from_bits(0x7ff8_0000_0000_0000)
+    } else if val == 0.0 {
+        0.0
+    } else {
+        val
+    }
 }
 
-impl VmValue {
-    /// Returns the byte size of the inner value.
-    pub fn byte_size(&self) -> usize {
-        match *self {
-            VmValue::I32(_) => 4,
-            VmValue::I64(_) => 8,
-            VmValue::F32(_) => 4,
-            VmValue::F64(_) => 8,
-            VmValue::i32Array2(_) => 8,
-            VmValue::i32Array4(_) => 16,
-            VmValue::i32Array8(_) => 32,
-            VmValue::i64Array2(_) => 16,
-            VmValue::i64Array4(_) => 32,
-            VmValue::i64Array8(_) => 64,
-            VmValue::f32Array2(_) => 8,
-            VmValue::f32Array4(_) => 16,
-            VmValue::f32Array8(_) => 32,
-            VmValue::f64Array2(_) => 8,
-            VmValue::f64Array4(_) => 16,
-            VmValue::f64Array8(_) => 32,
-            _ => panic!(),
-        }
-    }
-
-    pub fn is_positive(&self) -> bool {
-        match *self {
-            VmValue::I32(val) => val >= 0,
-            VmValue::I64(val) => val >= 0,
-            VmValue::F32(val) => val >= 0.0,
-            VmValue::F64(val) => val >= 0.0,
-            VmValue::i32Array2(val) => val.iter().all(|&v| v >= 0),
-            VmValue::i32Array4(val) => val.iter().all(|&v| v >= 0),
-            VmValue::i32Array8(val) => val.iter().all(|&v| v >= 0),
-            VmValue::i64Array2(val) => val.iter().all(|&v| v >= 0),
-            VmValue::i64Array4(val) => val.iter().all(|&v| v >= 0),
-            VmValue::i64Array8(val) => val.iter().all(|&v| v >= 0),
-            VmValue::f32Array2(val) => val.iter().all(|&v| v >= 0.0),
-            VmValue::f32Array4(val) => val.iter().all(|&v| v >= 0.0),
-            VmValue::f32Array8(val) => val.iter().all(|&v| v >= 0.0),
-            VmValue::f64Array2(val) => val.iter().all(|&v| v >= 0.0),
-            VmValue::f64Array4(val) => val.iter().all(|&v| v >= 0.0),
-            VmValue::f64Array8(val) => val.iter().all(|&v| v >= 0.0),
-            _ => panic!(),
+/// `f16` counterpart of `canonical_f32`, using binary16's own canonical
+/// quiet-NaN bit pattern.
+fn canonical_f16(val: f16) -> f16 {
+    if val.is_nan() {
+        f16::from_bits(0x7e00)
+    } else if val == f16::ZERO {
+        f16::ZERO
+    } else {
+        val
+    }
+}
+
+/// `bf16` counterpart of `canonical_f32`, using bfloat16's own canonical
+/// quiet-NaN bit pattern.
+fn canonical_bf16(val: bf16) -> bf16 {
+    if val.is_nan() {
+        bf16::from_bits(0x7fc0)
+    } else if val == bf16::ZERO {
+        bf16::ZERO
+    } else {
+        val
+    }
+}
+
+// Single-byte discriminants for `to_canonical_bytes`/`from_canonical_bytes`,
+// in the same order `VmValue` declares its variants. These are part of the
+// wire format two nodes hash against, so once assigned a tag must never be
+// reused for a different variant or reordered.
+const TAG_I32: u8 = 0;
+const TAG_I64: u8 = 1;
+const TAG_I128: u8 = 2;
+const TAG_U128: u8 = 3;
+const TAG_F32: u8 = 4;
+const TAG_F64: u8 = 5;
+const TAG_F16: u8 = 6;
+const TAG_BF16: u8 = 7;
+const TAG_FE25519: u8 = 8;
+const TAG_I32_ARRAY2: u8 = 9;
+const TAG_I32_ARRAY4: u8 = 10;
+const TAG_I32_ARRAY8: u8 = 11;
+const TAG_I32_ARRAY16: u8 = 12;
+const TAG_I32_ARRAY32: u8 = 13;
+const TAG_I32_ARRAY64: u8 = 14;
+const TAG_I32_ARRAY128: u8 = 15;
+const TAG_I32_ARRAY256: u8 = 16;
+const TAG_I64_ARRAY2: u8 = 17;
+const TAG_I64_ARRAY4: u8 = 18;
+const TAG_I64_ARRAY8: u8 = 19;
+const TAG_I64_ARRAY16: u8 = 20;
+const TAG_I64_ARRAY32: u8 = 21;
+const TAG_I64_ARRAY64: u8 = 22;
+const TAG_I64_ARRAY128: u8 = 23;
+const TAG_I64_ARRAY256: u8 = 24;
+const TAG_F32_ARRAY2: u8 = 25;
+const TAG_F32_ARRAY4: u8 = 26;
+const TAG_F32_ARRAY8: u8 = 27;
+const TAG_F32_ARRAY16: u8 = 28;
+const TAG_F32_ARRAY32: u8 = 29;
+const TAG_F32_ARRAY64: u8 = 30;
+const TAG_F32_ARRAY128: u8 = 31;
+const TAG_F32_ARRAY256: u8 = 32;
+const TAG_F64_ARRAY2: u8 = 33;
+const TAG_F64_ARRAY4: u8 = 34;
+const TAG_F64_ARRAY8: u8 = 35;
+const TAG_F64_ARRAY16: u8 = 36;
+const TAG_F64_ARRAY32: u8 = 37;
+const TAG_F64_ARRAY64: u8 = 38;
+const TAG_F64_ARRAY128: u8 = 39;
+const TAG_F64_ARRAY256: u8 = 40;
+const TAG_F16_ARRAY2: u8 = 41;
+const TAG_F16_ARRAY4: u8 = 42;
+const TAG_F16_ARRAY8: u8 = 43;
+const TAG_F16_ARRAY16: u8 = 44;
+const TAG_F16_ARRAY32: u8 = 45;
+const TAG_F16_ARRAY64: u8 = 46;
+const TAG_F16_ARRAY128: u8 = 47;
+const TAG_F16_ARRAY256: u8 = 48;
+const TAG_BF16_ARRAY2: u8 = 49;
+const TAG_BF16_ARRAY4: u8 = 50;
+const TAG_BF16_ARRAY8: u8 = 51;
+const TAG_BF16_ARRAY16: u8 = 52;
+const TAG_BF16_ARRAY32: u8 = 53;
+const TAG_BF16_ARRAY64: u8 = 54;
+const TAG_BF16_ARRAY128: u8 = 55;
+const TAG_BF16_ARRAY256: u8 = 56;
+
+/// Reduces a signed, possibly negative or oversized rotation count to
+/// `[0, len)` (`rem_euclid` rather than `%` so a negative `n` wraps around
+/// instead of producing a negative remainder); `len == 0` always normalizes
+/// to `0` since there is nothing to rotate.
+fn normalize_rotation(n: i64, len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    n.rem_euclid(len as i64) as usize
+}
+
+/// Left-rotates `slice` by `k` lanes in place via the three-reversal trick
+/// (reverse `[0..k]`, reverse `[k..len]`, reverse the whole slice) rather
+/// than copying into a temporary buffer: O(len) time, O(1) extra space.
+/// `k` is assumed already reduced to `[0, slice.len())` by
+/// `normalize_rotation`.
+fn rotate_left_in_place<T: Copy>(slice: &mut [T], k: usize) {
+    if slice.is_empty() || k == 0 {
+        return;
+    }
+
+    slice[..k].reverse();
+    slice[k..].reverse();
+    slice.reverse();
+}
+
+/// Rotates a fixed-size array `VmValue` variant left by `n` lanes, see
+/// `rotate_left_in_place`.
+macro_rules! rotate_left_arm {
+    ($variant:ident) => {
+        VmValue::$variant(mut val) => {
+            let k = normalize_rotation(n, val.len());
+            rotate_left_in_place(&mut val, k);
+            Ok(VmValue::$variant(val))
+        }
+    };
+}
+
+/// Appends a fixed-size integer array's lanes as little-endian bytes, tagged
+/// with `$tag`. The fixed width is implied by the tag alone, so unlike
+/// Borsh/BCS's `Vec<T>` encoding there is no length prefix to write.
+macro_rules! int_array_bytes_arm {
+    ($variant:ident, $tag:expr) => {
+        VmValue::$variant(val) => {
+            let mut bytes = vec![$tag];
+            for v in val.iter() {
+                bytes.extend_from_slice(&v.to_le_bytes());
+            }
+            bytes
+        }
+    };
+}
+
+/// Canonicalizes (NaN/negative-zero) then little-endian-encodes a `f32`/`f64`
+/// array, see `int_array_bytes_arm!`.
+macro_rules! float_array_bytes_arm {
+    ($variant:ident, $tag:expr, $canon:ident) => {
+        VmValue::$variant(val) => {
+            let mut bytes = vec![$tag];
+            for v in val.iter() {
+                bytes.extend_from_slice(&$canon(*v).to_le_bytes());
+            }
+            bytes
+        }
+    };
+}
+
+/// Canonicalizes then little-endian-encodes an `f16`/`bf16` array via the
+/// type's 16-bit `to_bits` representation, see `int_array_bytes_arm!`.
+macro_rules! half_array_bytes_arm {
+    ($variant:ident, $tag:expr, $canon:ident) => {
+        VmValue::$variant(val) => {
+            let mut bytes = vec![$tag];
+            for v in val.iter() {
+                bytes.extend_from_slice(&$canon(*v).to_bits().to_le_bytes());
+            }
+            bytes
+        }
+    };
+}
+
+/// Decodes a fixed-size integer or float array tagged `$tag` back out of
+/// `bytes` via the element type's own `from_le_bytes`.
+macro_rules! fixed_array_from_bytes_arm {
+    ($tag:expr, $out_variant:ident, $ty:ty, $size:expr, $n:expr) => {
+        $tag => {
+            let mut result: [$ty; $n] = [0 as $ty; $n];
+            for (i, slot) in result.iter_mut().enumerate() {
+                let start = i * $size;
+                let chunk = bytes
+                    .get(start..start + $size)
+                    .ok_or(VmError::TypeMismatch)?;
+                let mut buf = [0u8; $size];
+                buf.copy_from_slice(chunk);
+                *slot = <$ty>::from_le_bytes(buf);
+            }
+            Ok(VmValue::$out_variant(result))
+        }
+    };
+}
+
+/// Decodes an `f16`/`bf16` array tagged `$tag` back out of `bytes` via
+/// `from_bits`.
+macro_rules! half_array_from_bytes_arm {
+    ($tag:expr, $out_variant:ident, $ty:ident, $n:expr) => {
+        $tag => {
+            let mut result: [$ty; $n] = [$ty::ZERO; $n];
+            for (i, slot) in result.iter_mut().enumerate() {
+                let start = i * 2;
+                let chunk = bytes.get(start..start + 2).ok_or(VmError::TypeMismatch)?;
+                let mut buf = [0u8; 2];
+                buf.copy_from_slice(chunk);
+                *slot = $ty::from_bits(u16::from_le_bytes(buf));
+            }
+            Ok(VmValue::$out_variant(result))
+        }
+    };
+}
+
+impl VmValue {
+    /// Returns the byte size of the inner value.
+    pub fn byte_size(&self) -> Result<usize, VmError> {
+        match *self {
+            VmValue::I32(_) => Ok(4),
+            VmValue::I64(_) => Ok(8),
+            VmValue::I128(_) => Ok(16),
+            VmValue::U128(_) => Ok(16),
+            VmValue::F32(_) => Ok(4),
+            VmValue::F64(_) => Ok(8),
+            VmValue::F16(_) => Ok(2),
+            VmValue::Bf16(_) => Ok(2),
+            VmValue::Fe25519(_) => Ok(32),
+            byte_size_arm!(i32Array2, 4, 2),
+            byte_size_arm!(i32Array4, 4, 4),
+            byte_size_arm!(i32Array8, 4, 8),
+            byte_size_arm!(i32Array16, 4, 16),
+            byte_size_arm!(i32Array32, 4, 32),
+            byte_size_arm!(i32Array64, 4, 64),
+            byte_size_arm!(i32Array128, 4, 128),
+            byte_size_arm!(i32Array256, 4, 256),
+            byte_size_arm!(i64Array2, 8, 2),
+            byte_size_arm!(i64Array4, 8, 4),
+            byte_size_arm!(i64Array8, 8, 8),
+            byte_size_arm!(i64Array16, 8, 16),
+            byte_size_arm!(i64Array32, 8, 32),
+            byte_size_arm!(i64Array64, 8, 64),
+            byte_size_arm!(i64Array128, 8, 128),
+            byte_size_arm!(i64Array256, 8, 256),
+            byte_size_arm!(f32Array2, 4, 2),
+            byte_size_arm!(f32Array4, 4, 4),
+            byte_size_arm!(f32Array8, 4, 8),
+            byte_size_arm!(f32Array16, 4, 16),
+            byte_size_arm!(f32Array32, 4, 32),
+            byte_size_arm!(f32Array64, 4, 64),
+            byte_size_arm!(f32Array128, 4, 128),
+            byte_size_arm!(f32Array256, 4, 256),
+            byte_size_arm!(f64Array2, 8, 2),
+            byte_size_arm!(f64Array4, 8, 4),
+            byte_size_arm!(f64Array8, 8, 8),
+            byte_size_arm!(f64Array16, 8, 16),
+            byte_size_arm!(f64Array32, 8, 32),
+            byte_size_arm!(f64Array64, 8, 64),
+            byte_size_arm!(f64Array128, 8, 128),
+            byte_size_arm!(f64Array256, 8, 256),
+            byte_size_arm!(f16Array2, 2, 2),
+            byte_size_arm!(f16Array4, 2, 4),
+            byte_size_arm!(f16Array8, 2, 8),
+            byte_size_arm!(f16Array16, 2, 16),
+            byte_size_arm!(f16Array32, 2, 32),
+            byte_size_arm!(f16Array64, 2, 64),
+            byte_size_arm!(f16Array128, 2, 128),
+            byte_size_arm!(f16Array256, 2, 256),
+            byte_size_arm!(bf16Array2, 2, 2),
+            byte_size_arm!(bf16Array4, 2, 4),
+            byte_size_arm!(bf16Array8, 2, 8),
+            byte_size_arm!(bf16Array16, 2, 16),
+            byte_size_arm!(bf16Array32, 2, 32),
+            byte_size_arm!(bf16Array64, 2, 64),
+            byte_size_arm!(bf16Array128, 2, 128),
+            byte_size_arm!(bf16Array256, 2, 256),
+        }
+    }
+
+    /// Number of SIMD lanes `self` occupies: `1` for every scalar variant,
+    /// the array width for every array variant. Used by `fuel`'s per-opcode
+    /// metering so wider vector opcodes cost proportionally more.
+    pub(crate) fn lane_count(&self) -> u64 {
+        match *self {
+            VmValue::I32(_)
+            | VmValue::I64(_)
+            | VmValue::I128(_)
+            | VmValue::U128(_)
+            | VmValue::F32(_)
+            | VmValue::F64(_)
+            | VmValue::F16(_)
+            | VmValue::Bf16(_)
+            | VmValue::Fe25519(_) => 1,
+            lane_count_arm!(i32Array2, 2),
+            lane_count_arm!(i32Array4, 4),
+            lane_count_arm!(i32Array8, 8),
+            lane_count_arm!(i32Array16, 16),
+            lane_count_arm!(i32Array32, 32),
+            lane_count_arm!(i32Array64, 64),
+            lane_count_arm!(i32Array128, 128),
+            lane_count_arm!(i32Array256, 256),
+            lane_count_arm!(i64Array2, 2),
+            lane_count_arm!(i64Array4, 4),
+            lane_count_arm!(i64Array8, 8),
+            lane_count_arm!(i64Array16, 16),
+            lane_count_arm!(i64Array32, 32),
+            lane_count_arm!(i64Array64, 64),
+            lane_count_arm!(i64Array128, 128),
+            lane_count_arm!(i64Array256, 256),
+            lane_count_arm!(f32Array2, 2),
+            lane_count_arm!(f32Array4, 4),
+            lane_count_arm!(f32Array8, 8),
+            lane_count_arm!(f32Array16, 16),
+            lane_count_arm!(f32Array32, 32),
+            lane_count_arm!(f32Array64, 64),
+            lane_count_arm!(f32Array128, 128),
+            lane_count_arm!(f32Array256, 256),
+            lane_count_arm!(f64Array2, 2),
+            lane_count_arm!(f64Array4, 4),
+            lane_count_arm!(f64Array8, 8),
+            lane_count_arm!(f64Array16, 16),
+            lane_count_arm!(f64Array32, 32),
+            lane_count_arm!(f64Array64, 64),
+            lane_count_arm!(f64Array128, 128),
+            lane_count_arm!(f64Array256, 256),
+            lane_count_arm!(f16Array2, 2),
+            lane_count_arm!(f16Array4, 4),
+            lane_count_arm!(f16Array8, 8),
+            lane_count_arm!(f16Array16, 16),
+            lane_count_arm!(f16Array32, 32),
+            lane_count_arm!(f16Array64, 64),
+            lane_count_arm!(f16Array128, 128),
+            lane_count_arm!(f16Array256, 256),
+            lane_count_arm!(bf16Array2, 2),
+            lane_count_arm!(bf16Array4, 4),
+            lane_count_arm!(bf16Array8, 8),
+            lane_count_arm!(bf16Array16, 16),
+            lane_count_arm!(bf16Array32, 32),
+            lane_count_arm!(bf16Array64, 64),
+            lane_count_arm!(bf16Array128, 128),
+            lane_count_arm!(bf16Array256, 256),
+        }
+    }
+
+    /// The scalar element type underlying `self`, independent of lane count;
+    /// see `describe`/`VmTypeDesc`.
+    pub fn element_kind(&self) -> VmElementKind {
+        match *self {
+            VmValue::I32(_) => VmElementKind::I32,
+            VmValue::I64(_) => VmElementKind::I64,
+            VmValue::I128(_) => VmElementKind::I128,
+            VmValue::U128(_) => VmElementKind::U128,
+            VmValue::F32(_) => VmElementKind::F32,
+            VmValue::F64(_) => VmElementKind::F64,
+            VmValue::F16(_) => VmElementKind::F16,
+            VmValue::Bf16(_) => VmElementKind::Bf16,
+            VmValue::Fe25519(_) => VmElementKind::Fe25519,
+            element_kind_arm!(i32Array2, VmElementKind::I32),
+            element_kind_arm!(i32Array4, VmElementKind::I32),
+            element_kind_arm!(i32Array8, VmElementKind::I32),
+            element_kind_arm!(i32Array16, VmElementKind::I32),
+            element_kind_arm!(i32Array32, VmElementKind::I32),
+            element_kind_arm!(i32Array64, VmElementKind::I32),
+            element_kind_arm!(i32Array128, VmElementKind::I32),
+            element_kind_arm!(i32Array256, VmElementKind::I32),
+            element_kind_arm!(i64Array2, VmElementKind::I64),
+            element_kind_arm!(i64Array4, VmElementKind::I64),
+            element_kind_arm!(i64Array8, VmElementKind::I64),
+            element_kind_arm!(i64Array16, VmElementKind::I64),
+            element_kind_arm!(i64Array32, VmElementKind::I64),
+            element_kind_arm!(i64Array64, VmElementKind::I64),
+            element_kind_arm!(i64Array128, VmElementKind::I64),
+            element_kind_arm!(i64Array256, VmElementKind::I64),
+            element_kind_arm!(f32Array2, VmElementKind::F32),
+            element_kind_arm!(f32Array4, VmElementKind::F32),
+            element_kind_arm!(f32Array8, VmElementKind::F32),
+            element_kind_arm!(f32Array16, VmElementKind::F32),
+            element_kind_arm!(f32Array32, VmElementKind::F32),
+            element_kind_arm!(f32Array64, VmElementKind::F32),
+            element_kind_arm!(f32Array128, VmElementKind::F32),
+            element_kind_arm!(f32Array256, VmElementKind::F32),
+            element_kind_arm!(f64Array2, VmElementKind::F64),
+            element_kind_arm!(f64Array4, VmElementKind::F64),
+            element_kind_arm!(f64Array8, VmElementKind::F64),
+            element_kind_arm!(f64Array16, VmElementKind::F64),
+            element_kind_arm!(f64Array32, VmElementKind::F64),
+            element_kind_arm!(f64Array64, VmElementKind::F64),
+            element_kind_arm!(f64Array128, VmElementKind::F64),
+            element_kind_arm!(f64Array256, VmElementKind::F64),
+            element_kind_arm!(f16Array2, VmElementKind::F16),
+            element_kind_arm!(f16Array4, VmElementKind::F16),
+            element_kind_arm!(f16Array8, VmElementKind::F16),
+            element_kind_arm!(f16Array16, VmElementKind::F16),
+            element_kind_arm!(f16Array32, VmElementKind::F16),
+            element_kind_arm!(f16Array64, VmElementKind::F16),
+            element_kind_arm!(f16Array128, VmElementKind::F16),
+            element_kind_arm!(f16Array256, VmElementKind::F16),
+            element_kind_arm!(bf16Array2, VmElementKind::Bf16),
+            element_kind_arm!(bf16Array4, VmElementKind::Bf16),
+            element_kind_arm!(bf16Array8, VmElementKind::Bf16),
+            element_kind_arm!(bf16Array16, VmElementKind::Bf16),
+            element_kind_arm!(bf16Array32, VmElementKind::Bf16),
+            element_kind_arm!(bf16Array64, VmElementKind::Bf16),
+            element_kind_arm!(bf16Array128, VmElementKind::Bf16),
+            element_kind_arm!(bf16Array256, VmElementKind::Bf16),
+        }
+    }
+
+    /// The structured, machine-readable shape of `self` — see `VmTypeDesc`.
+    pub fn describe(&self) -> VmTypeDesc {
+        VmTypeDesc {
+            kind: self.element_kind(),
+            lane_count: self.lane_count() as usize,
+            byte_size: self.byte_size().unwrap(),
+        }
+    }
+
+    /// Each lane's `Display` output, one string per lane (length-1 for every
+    /// scalar variant) — the shared formatting step behind `render`.
+    fn lane_strings(&self) -> Vec<String> {
+        match *self {
+            VmValue::I32(val) => vec![format!("{}", val)],
+            VmValue::I64(val) => vec![format!("{}", val)],
+            VmValue::I128(val) => vec![format!("{}", val)],
+            VmValue::U128(val) => vec![format!("{}", val)],
+            VmValue::F32(val) => vec![format!("{}", val)],
+            VmValue::F64(val) => vec![format!("{}", val)],
+            VmValue::F16(val) => vec![format!("{}", val)],
+            VmValue::Bf16(val) => vec![format!("{}", val)],
+            VmValue::Fe25519(val) => vec![format!("{:?}", fe25519::freeze(&val))],
+            lane_strings_arm!(i32Array2),
+            lane_strings_arm!(i32Array4),
+            lane_strings_arm!(i32Array8),
+            lane_strings_arm!(i32Array16),
+            lane_strings_arm!(i32Array32),
+            lane_strings_arm!(i32Array64),
+            lane_strings_arm!(i32Array128),
+            lane_strings_arm!(i32Array256),
+            lane_strings_arm!(i64Array2),
+            lane_strings_arm!(i64Array4),
+            lane_strings_arm!(i64Array8),
+            lane_strings_arm!(i64Array16),
+            lane_strings_arm!(i64Array32),
+            lane_strings_arm!(i64Array64),
+            lane_strings_arm!(i64Array128),
+            lane_strings_arm!(i64Array256),
+            lane_strings_arm!(f32Array2),
+            lane_strings_arm!(f32Array4),
+            lane_strings_arm!(f32Array8),
+            lane_strings_arm!(f32Array16),
+            lane_strings_arm!(f32Array32),
+            lane_strings_arm!(f32Array64),
+            lane_strings_arm!(f32Array128),
+            lane_strings_arm!(f32Array256),
+            lane_strings_arm!(f64Array2),
+            lane_strings_arm!(f64Array4),
+            lane_strings_arm!(f64Array8),
+            lane_strings_arm!(f64Array16),
+            lane_strings_arm!(f64Array32),
+            lane_strings_arm!(f64Array64),
+            lane_strings_arm!(f64Array128),
+            lane_strings_arm!(f64Array256),
+            lane_strings_arm!(f16Array2),
+            lane_strings_arm!(f16Array4),
+            lane_strings_arm!(f16Array8),
+            lane_strings_arm!(f16Array16),
+            lane_strings_arm!(f16Array32),
+            lane_strings_arm!(f16Array64),
+            lane_strings_arm!(f16Array128),
+            lane_strings_arm!(f16Array256),
+            lane_strings_arm!(bf16Array2),
+            lane_strings_arm!(bf16Array4),
+            lane_strings_arm!(bf16Array8),
+            lane_strings_arm!(bf16Array16),
+            lane_strings_arm!(bf16Array32),
+            lane_strings_arm!(bf16Array64),
+            lane_strings_arm!(bf16Array128),
+            lane_strings_arm!(bf16Array256),
+        }
+    }
+
+    /// Formats `self`'s value without `impl Debug`'s `describe()` prefix, with style metadata
+    /// (`RenderStyle`) attached: a distinct `Color` for scalars vs. integer
+    /// arrays vs. float arrays, and elision past `style.max_elements` lanes
+    /// (`[1, 2, 3, … +250 more]`) so dumping a large array in a debugger
+    /// doesn't flood the screen. `RenderMode::Plain` carries no color, just
+    /// the same elided text `to_plain()` would produce.
+    pub fn render(&self, style: RenderStyle) -> StyledText {
+        let desc = self.describe();
+        let lanes = self.lane_strings();
+
+        let body = if desc.lane_count == 1 {
+            lanes[0].clone()
+        } else if lanes.len() > style.max_elements {
+            let shown = lanes[..style.max_elements].join(", ");
+            format!("[{}, … +{} more]", shown, lanes.len() - style.max_elements)
+        } else {
+            format!("[{}]", lanes.join(", "))
+        };
+
+        let color = match style.mode {
+            RenderMode::Plain => Color::Default,
+            RenderMode::Ansi | RenderMode::Components => {
+                if desc.lane_count == 1 {
+                    Color::Scalar
+                } else {
+                    match desc.kind {
+                        VmElementKind::I32 | VmElementKind::I64 => Color::IntArray,
+                        _ => Color::FloatArray,
+                    }
+                }
+            }
+        };
+
+        StyledText {
+            spans: vec![StyledSpan {
+                text: body,
+                color,
+                bold: false,
+                dim: false,
+            }],
+        }
+    }
+
+    pub fn is_positive(&self) -> Result<bool, VmError> {
+        match *self {
+            VmValue::I32(val) => Ok(val >= 0),
+            VmValue::I64(val) => Ok(val >= 0),
+            VmValue::I128(val) => Ok(val >= 0),
+            VmValue::U128(_) => Ok(true),
+            VmValue::F32(val) => Ok(val >= 0.0),
+            VmValue::F64(val) => Ok(val >= 0.0),
+            VmValue::F16(val) => Ok(val >= f16::ZERO),
+            VmValue::Bf16(val) => Ok(val >= bf16::ZERO),
+            // A field element has no sign; asking whether one is "positive"
+            // is as meaningless as asking the same of an array.
+            VmValue::Fe25519(_) => Err(VmError::UnsupportedOperation),
+            is_positive_int_arm!(i32Array2),
+            is_positive_int_arm!(i32Array4),
+            is_positive_int_arm!(i32Array8),
+            is_positive_int_arm!(i32Array16),
+            is_positive_int_arm!(i32Array32),
+            is_positive_int_arm!(i32Array64),
+            is_positive_int_arm!(i32Array128),
+            is_positive_int_arm!(i32Array256),
+            is_positive_int_arm!(i64Array2),
+            is_positive_int_arm!(i64Array4),
+            is_positive_int_arm!(i64Array8),
+            is_positive_int_arm!(i64Array16),
+            is_positive_int_arm!(i64Array32),
+            is_positive_int_arm!(i64Array64),
+            is_positive_int_arm!(i64Array128),
+            is_positive_int_arm!(i64Array256),
+            is_positive_float_arm!(f32Array2),
+            is_positive_float_arm!(f32Array4),
+            is_positive_float_arm!(f32Array8),
+            is_positive_float_arm!(f32Array16),
+            is_positive_float_arm!(f32Array32),
+            is_positive_float_arm!(f32Array64),
+            is_positive_float_arm!(f32Array128),
+            is_positive_float_arm!(f32Array256),
+            is_positive_float_arm!(f64Array2),
+            is_positive_float_arm!(f64Array4),
+            is_positive_float_arm!(f64Array8),
+            is_positive_float_arm!(f64Array16),
+            is_positive_float_arm!(f64Array32),
+            is_positive_float_arm!(f64Array64),
+            is_positive_float_arm!(f64Array128),
+            is_positive_float_arm!(f64Array256),
+            is_positive_half_arm!(f16Array2, f16::ZERO),
+            is_positive_half_arm!(f16Array4, f16::ZERO),
+            is_positive_half_arm!(f16Array8, f16::ZERO),
+            is_positive_half_arm!(f16Array16, f16::ZERO),
+            is_positive_half_arm!(f16Array32, f16::ZERO),
+            is_positive_half_arm!(f16Array64, f16::ZERO),
+            is_positive_half_arm!(f16Array128, f16::ZERO),
+            is_positive_half_arm!(f16Array256, f16::ZERO),
+            is_positive_half_arm!(bf16Array2, bf16::ZERO),
+            is_positive_half_arm!(bf16Array4, bf16::ZERO),
+            is_positive_half_arm!(bf16Array8, bf16::ZERO),
+            is_positive_half_arm!(bf16Array16, bf16::ZERO),
+            is_positive_half_arm!(bf16Array32, bf16::ZERO),
+            is_positive_half_arm!(bf16Array64, bf16::ZERO),
+            is_positive_half_arm!(bf16Array128, bf16::ZERO),
+            is_positive_half_arm!(bf16Array256, bf16::ZERO),
+        }
+    }
+
+    fn check_f32_infinite(val: f32) -> Option<f32> {
+        if val.is_infinite() {
+            None
+        } else {
+            Some(val)
+        }
+    }
+
+    fn check_f64_infinite(val: f64) -> Option<f64> {
+        if val.is_infinite() {
+            None
+        } else {
+            Some(val)
+        }
+    }
+
+    #[cfg(not(feature = "softfloat"))]
+    fn sum_f32(val1: &f32, val2: &f32) -> Option<f32> {
+        VmValue::check_f32_infinite(val1 + val2)
+    }
+
+    #[cfg(feature = "softfloat")]
+    fn sum_f32(val1: &f32, val2: &f32) -> Option<f32> {
+        VmValue::check_f32_infinite(softfloat::add_f32(*val1, *val2))
+    }
+
+    #[cfg(not(feature = "softfloat"))]
+    fn sum_f64(val1: &f64, val2: &f64) -> Option<f64> {
+        VmValue::check_f64_infinite(val1 + val2)
+    }
+
+    #[cfg(feature = "softfloat")]
+    fn sum_f64(val1: &f64, val2: &f64) -> Option<f64> {
+        VmValue::check_f64_infinite(softfloat::add_f64(*val1, *val2))
+    }
+
+    #[cfg(not(feature = "softfloat"))]
+    fn sub_f32(val1: &f32, val2: &f32) -> Option<f32> {
+        VmValue::check_f32_infinite(val1 - val2)
+    }
+
+    #[cfg(feature = "softfloat")]
+    fn sub_f32(val1: &f32, val2: &f32) -> Option<f32> {
+        VmValue::check_f32_infinite(softfloat::sub_f32(*val1, *val2))
+    }
+
+    #[cfg(not(feature = "softfloat"))]
+    fn sub_f64(val1: &f64, val2: &f64) -> Option<f64> {
+        VmValue::check_f64_infinite(val1 - val2)
+    }
+
+    #[cfg(feature = "softfloat")]
+    fn sub_f64(val1: &f64, val2: &f64) -> Option<f64> {
+        VmValue::check_f64_infinite(softfloat::sub_f64(*val1, *val2))
+    }
+
+    #[cfg(not(feature = "softfloat"))]
+    fn mul_f32(val1: &f32, val2: &f32) -> Option<f32> {
+        VmValue::check_f32_infinite(val1 * val2)
+    }
+
+    #[cfg(feature = "softfloat")]
+    fn mul_f32(val1: &f32, val2: &f32) -> Option<f32> {
+        VmValue::check_f32_infinite(softfloat::mul_f32(*val1, *val2))
+    }
+
+    #[cfg(not(feature = "softfloat"))]
+    fn mul_f64(val1: &f64, val2: &f64) -> Option<f64> {
+        VmValue::check_f64_infinite(val1 * val2)
+    }
+
+    #[cfg(feature = "softfloat")]
+    fn mul_f64(val1: &f64, val2: &f64) -> Option<f64> {
+        VmValue::check_f64_infinite(softfloat::mul_f64(*val1, *val2))
+    }
+
+    #[cfg(not(feature = "softfloat"))]
+    fn div_f32(val1: &f32, val2: &f32) -> Option<f32> {
+        if *val2 == 0.0 {
+            return None;
+        }
+        VmValue::check_f32_infinite(val1 / val2)
+    }
+
+    #[cfg(feature = "softfloat")]
+    fn div_f32(val1: &f32, val2: &f32) -> Option<f32> {
+        if *val2 == 0.0 {
+            return None;
+        }
+        VmValue::check_f32_infinite(softfloat::div_f32(*val1, *val2))
+    }
+
+    #[cfg(not(feature = "softfloat"))]
+    fn div_f64(val1: &f64, val2: &f64) -> Option<f64> {
+        if *val2 == 0.0 {
+            return None;
+        }
+        VmValue::check_f64_infinite(val1 / val2)
+    }
+
+    #[cfg(feature = "softfloat")]
+    fn div_f64(val1: &f64, val2: &f64) -> Option<f64> {
+        if *val2 == 0.0 {
+            return None;
+        }
+        VmValue::check_f64_infinite(softfloat::div_f64(*val1, *val2))
+    }
+
+    #[cfg(not(feature = "softfloat"))]
+    fn rem_f32(val1: &f32, val2: &f32) -> Option<f32> {
+        if *val2 == 0.0 {
+            return None;
+        }
+        VmValue::check_f32_infinite(val1 % val2)
+    }
+
+    #[cfg(feature = "softfloat")]
+    fn rem_f32(val1: &f32, val2: &f32) -> Option<f32> {
+        if *val2 == 0.0 {
+            return None;
+        }
+        VmValue::check_f32_infinite(softfloat::rem_f32(*val1, *val2))
+    }
+
+    #[cfg(not(feature = "softfloat"))]
+    fn rem_f64(val1: &f64, val2: &f64) -> Option<f64> {
+        if *val2 == 0.0 {
+            return None;
+        }
+        VmValue::check_f64_infinite(val1 % val2)
+    }
+
+    #[cfg(feature = "softfloat")]
+    fn rem_f64(val1: &f64, val2: &f64) -> Option<f64> {
+        if *val2 == 0.0 {
+            return None;
+        }
+        VmValue::check_f64_infinite(softfloat::rem_f64(*val1, *val2))
+    }
+
+    /// Portable 128-bit unsigned shift-subtract long division, returning
+    /// `(quotient, remainder)`. Used instead of the native `u128` divide so
+    /// that every validator computes bit-identical results regardless of
+    /// whether the target has hardware 128-bit division.
+    fn udivmod128(n: u128, d: u128) -> Result<(u128, u128), VmError> {
+        const B: i64 = 128;
+
+        if d == 0 {
+            return Err(VmError::DivideByZero);
+        }
+
+        if n == 0 {
+            return Ok((0, 0));
+        }
+
+        let mut sr = d.leading_zeros() as i64 - n.leading_zeros() as i64;
+
+        if sr < 0 {
+            // d > n
+            return Ok((0, n));
+        }
+
+        if sr == B - 1 {
+            // d == 1
+            return Ok((n, 0));
+        }
+
+        sr += 1;
+
+        let mut q = n << (B - sr) as u32;
+        let mut r = n >> sr as u32;
+        let mut carry: u128 = 0;
+
+        for _ in 0..sr {
+            r = (r << 1) | (q >> (B - 1) as u32);
+            q = (q << 1) | carry;
+
+            let s = (d.wrapping_sub(r).wrapping_sub(1) as i128 >> (B - 1) as u32) as u128;
+            carry = s & 1;
+            r = r.wrapping_sub(d & s);
+        }
+
+        q = (q << 1) | carry;
+
+        Ok((q, r))
+    }
+
+    /// Signed 128-bit division built on top of `udivmod128` by taking
+    /// absolute values and reapplying the XOR of the input signs.
+    fn sdivmod128(n: i128, d: i128) -> Result<(i128, i128), VmError> {
+        if d == 0 {
+            return Err(VmError::DivideByZero);
+        }
+
+        if n == i128::MIN && d == -1 {
+            return Err(VmError::Overflow);
+        }
+
+        let quotient_negative = (n < 0) ^ (d < 0);
+        let (uq, ur) = VmValue::udivmod128(n.unsigned_abs(), d.unsigned_abs())?;
+
+        let q = if quotient_negative { -(uq as i128) } else { uq as i128 };
+        let r = if n < 0 { -(ur as i128) } else { ur as i128 };
+
+        Ok((q, r))
+    }
+
+    /// The witness bases that Pomerance/Selfridge/Wagstaff proved correctly
+    /// classify every `u64` under Miller-Rabin, so this test is deterministic
+    /// rather than probabilistic.
+    const MILLER_RABIN_WITNESSES: [u64; 12] =
+        [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    /// Deterministic Miller-Rabin primality test for `I32`/`I64` values,
+    /// returning `VmValue::I32(1)` if `self` is prime and `VmValue::I32(0)`
+    /// otherwise. Every `u64 mul mod n` widens to `u128` so no intermediate
+    /// can overflow.
+    pub fn is_prime(&self) -> Result<VmValue, VmError> {
+        let n: u64 = match *self {
+            VmValue::I32(val) if val >= 0 => val as u64,
+            VmValue::I32(_) => return Ok(VmValue::I32(0)),
+            VmValue::I64(val) if val >= 0 => val as u64,
+            VmValue::I64(_) => return Ok(VmValue::I32(0)),
+            _ => return Err(VmError::UnsupportedOperation),
+        };
+
+        Ok(VmValue::I32(Self::miller_rabin(n) as i32))
+    }
+
+    fn miller_rabin(n: u64) -> bool {
+        if n < 2 {
+            return false;
+        }
+
+        for &p in &Self::MILLER_RABIN_WITNESSES {
+            if n == p {
+                return true;
+            }
+        }
+
+        if n % 2 == 0 {
+            return false;
+        }
+
+        // Write n - 1 = d * 2^s with d odd.
+        let mut d = n - 1;
+        let mut s = 0u32;
+        while d % 2 == 0 {
+            d /= 2;
+            s += 1;
+        }
+
+        'witness: for &a in &Self::MILLER_RABIN_WITNESSES {
+            let mut x = Self::mod_pow(a % n, d, n);
+
+            if x == 1 || x == n - 1 {
+                continue;
+            }
+
+            for _ in 0..s.saturating_sub(1) {
+                x = Self::mul_mod(x, x, n);
+
+                if x == n - 1 {
+                    continue 'witness;
+                }
+            }
+
+            return false;
+        }
+
+        true
+    }
+
+    /// `(a * b) mod n` with the multiplication widened to `u128` so it can
+    /// never overflow regardless of how close `a`/`b` are to `u64::MAX`.
+    fn mul_mod(a: u64, b: u64, n: u64) -> u64 {
+        ((a as u128 * b as u128) % n as u128) as u64
+    }
+
+    /// `(base^exp) mod n` via square-and-multiply, using `mul_mod` for every
+    /// multiplication so intermediates never overflow.
+    fn mod_pow(mut base: u64, mut exp: u64, n: u64) -> u64 {
+        let mut result = 1u64;
+        base %= n;
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = Self::mul_mod(result, base, n);
+            }
+
+            exp >>= 1;
+            base = Self::mul_mod(base, base, n);
+        }
+
+        result
+    }
+
+    /// Deterministic square root, computed via a seeded Newton-Raphson
+    /// iteration so the result is reproducible across platforms. Array
+    /// variants apply element-wise.
+    pub fn sqrt(&self) -> Result<VmValue, VmError> {
+        self.map_float(transcendental::sqrt_f32, transcendental::sqrt_f64)
+    }
+
+    /// Deterministic sine, computed via argument reduction modulo pi/2
+    /// followed by a fixed-degree Taylor polynomial (see `transcendental::sin_poly`).
+    pub fn sin(&self) -> Result<VmValue, VmError> {
+        self.map_float(transcendental::sin_f32, transcendental::sin_f64)
+    }
+
+    /// Deterministic cosine, see `sin`.
+    pub fn cos(&self) -> Result<VmValue, VmError> {
+        self.map_float(transcendental::cos_f32, transcendental::cos_f64)
+    }
+
+    /// Rounds towards negative infinity via direct exponent/mantissa bit
+    /// manipulation.
+    pub fn floor(&self) -> Result<VmValue, VmError> {
+        self.map_float(transcendental::floor_f32, transcendental::floor_f64)
+    }
+
+    /// Rounds towards positive infinity, see `floor`.
+    pub fn ceil(&self) -> Result<VmValue, VmError> {
+        self.map_float(transcendental::ceil_f32, transcendental::ceil_f64)
+    }
+
+    /// Scales `self` by `2^exp` via direct exponent manipulation.
+    pub fn scalbn(&self, exp: i32) -> Result<VmValue, VmError> {
+        match *self {
+            VmValue::F32(val) => Self::reject_nan_inf_f32(val)
+                .map(|val| transcendental::scalbn_f32(val, exp))
+                .map(VmValue::F32),
+            VmValue::F64(val) => Self::reject_nan_inf_f64(val)
+                .map(|val| transcendental::scalbn_f64(val, exp))
+                .map(VmValue::F64),
+            _ => Err(VmError::UnsupportedOperation),
+        }
+    }
+
+    /// Returns `self` with the sign bit of `other`.
+    pub fn copysign(&self, other: &VmValue) -> Result<VmValue, VmError> {
+        match (*self, *other) {
+            (VmValue::F32(val), VmValue::F32(sign_src)) => Self::reject_nan_inf_f32(val)
+                .map(|val| transcendental::copysign_f32(val, sign_src))
+                .map(VmValue::F32),
+            (VmValue::F64(val), VmValue::F64(sign_src)) => Self::reject_nan_inf_f64(val)
+                .map(|val| transcendental::copysign_f64(val, sign_src))
+                .map(VmValue::F64),
+            _ => Err(VmError::UnsupportedOperation),
+        }
+    }
+
+    /// Applies a unary float op to `self`, dispatching to the `f32`/`f64`
+    /// variant (scalar or 2/4/8-lane array) and rejecting NaN/infinite inputs
+    /// with `VmError::NotANumber`/`VmError::Infinity`.
+    fn map_float(
+        &self,
+        f32_op: fn(f32) -> f32,
+        f64_op: fn(f64) -> f64,
+    ) -> Result<VmValue, VmError> {
+        match *self {
+            VmValue::F32(val) => Self::reject_nan_inf_f32(val).map(f32_op).map(VmValue::F32),
+            VmValue::F64(val) => Self::reject_nan_inf_f64(val).map(f64_op).map(VmValue::F64),
+            VmValue::f32Array2(val) => Self::map_f32_2(&val, f32_op).map(VmValue::f32Array2),
+            VmValue::f32Array4(val) => Self::map_f32_4(&val, f32_op).map(VmValue::f32Array4),
+            VmValue::f32Array8(val) => Self::map_f32_8(&val, f32_op).map(VmValue::f32Array8),
+            VmValue::f64Array2(val) => Self::map_f64_2(&val, f64_op).map(VmValue::f64Array2),
+            VmValue::f64Array4(val) => Self::map_f64_4(&val, f64_op).map(VmValue::f64Array4),
+            VmValue::f64Array8(val) => Self::map_f64_8(&val, f64_op).map(VmValue::f64Array8),
+            _ => Err(VmError::UnsupportedOperation),
+        }
+    }
+
+    fn reject_nan_inf_f32(val: f32) -> Result<f32, VmError> {
+        if val.is_nan() {
+            Err(VmError::NotANumber)
+        } else if val.is_infinite() {
+            Err(VmError::Infinity)
+        } else {
+            Ok(val)
+        }
+    }
+
+    fn reject_nan_inf_f64(val: f64) -> Result<f64, VmError> {
+        if val.is_nan() {
+            Err(VmError::NotANumber)
+        } else if val.is_infinite() {
+            Err(VmError::Infinity)
+        } else {
+            Ok(val)
+        }
+    }
+
+    fn map_f32_2(val: &[f32; 2], f: fn(f32) -> f32) -> Result<[f32; 2], VmError> {
+        let mut result: [f32; 2] = [0.0; 2];
+        for (r, v) in result.iter_mut().zip(val.iter()) {
+            *r = f(Self::reject_nan_inf_f32(*v)?);
+        }
+        Ok(result)
+    }
+
+    fn map_f32_4(val: &[f32; 4], f: fn(f32) -> f32) -> Result<[f32; 4], VmError> {
+        let mut result: [f32; 4] = [0.0; 4];
+        for (r, v) in result.iter_mut().zip(val.iter()) {
+            *r = f(Self::reject_nan_inf_f32(*v)?);
+        }
+        Ok(result)
+    }
+
+    fn map_f32_8(val: &[f32; 8], f: fn(f32) -> f32) -> Result<[f32; 8], VmError> {
+        let mut result: [f32; 8] = [0.0; 8];
+        for (r, v) in result.iter_mut().zip(val.iter()) {
+            *r = f(Self::reject_nan_inf_f32(*v)?);
+        }
+        Ok(result)
+    }
+
+    fn map_f64_2(val: &[f64; 2], f: fn(f64) -> f64) -> Result<[f64; 2], VmError> {
+        let mut result: [f64; 2] = [0.0; 2];
+        for (r, v) in result.iter_mut().zip(val.iter()) {
+            *r = f(Self::reject_nan_inf_f64(*v)?);
+        }
+        Ok(result)
+    }
+
+    fn map_f64_4(val: &[f64; 4], f: fn(f64) -> f64) -> Result<[f64; 4], VmError> {
+        let mut result: [f64; 4] = [0.0; 4];
+        for (r, v) in result.iter_mut().zip(val.iter()) {
+            *r = f(Self::reject_nan_inf_f64(*v)?);
+        }
+        Ok(result)
+    }
+
+    fn map_f64_8(val: &[f64; 8], f: fn(f64) -> f64) -> Result<[f64; 8], VmError> {
+        let mut result: [f64; 8] = [0.0; 8];
+        for (r, v) in result.iter_mut().zip(val.iter()) {
+            *r = f(Self::reject_nan_inf_f64(*v)?);
+        }
+        Ok(result)
+    }
+
+    /// Fallible equality check for callers (e.g. the VM's comparison
+    /// opcodes) that need to distinguish "not equal" from "not comparable",
+    /// rather than `PartialEq::eq`'s blanket `false` for mismatched variants.
+    pub fn try_eq(&self, other: &VmValue) -> Result<bool, VmError> {
+        if std::mem::discriminant(self) != std::mem::discriminant(other) {
+            return Err(VmError::TypeMismatch);
+        }
+
+        Ok(self == other)
+    }
+
+    /// Fallible ordering check, surfacing `VmError::TypeMismatch` for
+    /// mismatched variants and `VmError::UnsupportedOperation` for the array
+    /// variants (which have no total order), rather than `PartialOrd`'s
+    /// blanket `None` for both cases.
+    pub fn try_partial_cmp(&self, other: &VmValue) -> Result<Ordering, VmError> {
+        if std::mem::discriminant(self) != std::mem::discriminant(other) {
+            return Err(VmError::TypeMismatch);
+        }
+
+        self.partial_cmp(other).ok_or(VmError::UnsupportedOperation)
+    }
+
+    /// Multiplies under the given `ArithMode`; opcodes dispatch here instead
+    /// of `impl Mul` directly when the contract has selected a non-default
+    /// overflow policy.
+    pub fn mul_mode(self, other: VmValue, mode: ArithMode) -> Result<VmValue, VmError> {
+        match mode {
+            ArithMode::Checked => self.mul(other),
+            ArithMode::Wrapping => self.wrapping_mul(other),
+            ArithMode::Saturating => self.saturating_mul(other),
+        }
+    }
+
+    /// Divides under the given `ArithMode`, see `mul_mode`.
+    pub fn div_mode(self, other: VmValue, mode: ArithMode) -> Result<VmValue, VmError> {
+        match mode {
+            ArithMode::Checked => self.div(other),
+            ArithMode::Wrapping => self.wrapping_div(other),
+            ArithMode::Saturating => self.saturating_div(other),
+        }
+    }
+
+    /// Takes the remainder under the given `ArithMode`, see `mul_mode`.
+    pub fn rem_mode(self, other: VmValue, mode: ArithMode) -> Result<VmValue, VmError> {
+        match mode {
+            ArithMode::Checked => self.rem(other),
+            ArithMode::Wrapping => self.wrapping_rem(other),
+            ArithMode::Saturating => self.saturating_rem(other),
+        }
+    }
+
+    /// Multiplication that wraps around modulo the integer type's width
+    /// instead of erroring on overflow. Non-integer variants fall back to
+    /// `impl Mul`'s checked behavior (see `ArithMode`).
+    pub fn wrapping_mul(self, other: VmValue) -> Result<VmValue, VmError> {
+        match (self, other) {
+            (VmValue::I32(val1), VmValue::I32(val2)) => Ok(VmValue::I32(val1.wrapping_mul(val2))),
+            (VmValue::I64(val1), VmValue::I64(val2)) => Ok(VmValue::I64(val1.wrapping_mul(val2))),
+            (VmValue::I128(val1), VmValue::I128(val2)) => {
+                Ok(VmValue::I128(val1.wrapping_mul(val2)))
+            }
+            (VmValue::U128(val1), VmValue::U128(val2)) => {
+                Ok(VmValue::U128(val1.wrapping_mul(val2)))
+            }
+            (_, _) => self.mul(other),
+        }
+    }
+
+    /// Multiplication that clamps to the integer type's min/max instead of
+    /// erroring on overflow. Non-integer variants fall back to `impl Mul`'s
+    /// checked behavior (see `ArithMode`).
+    pub fn saturating_mul(self, other: VmValue) -> Result<VmValue, VmError> {
+        match (self, other) {
+            (VmValue::I32(val1), VmValue::I32(val2)) => {
+                Ok(VmValue::I32(val1.saturating_mul(val2)))
+            }
+            (VmValue::I64(val1), VmValue::I64(val2)) => {
+                Ok(VmValue::I64(val1.saturating_mul(val2)))
+            }
+            (VmValue::I128(val1), VmValue::I128(val2)) => {
+                Ok(VmValue::I128(val1.saturating_mul(val2)))
+            }
+            (VmValue::U128(val1), VmValue::U128(val2)) => {
+                Ok(VmValue::U128(val1.saturating_mul(val2)))
+            }
+            (_, _) => self.mul(other),
+        }
+    }
+
+    /// Division that wraps on overflow instead of erroring. Divide-by-zero
+    /// is still an error under every `ArithMode`: there is no well-defined
+    /// wrapped or saturated quotient for it. The only integer division that
+    /// can overflow is `MIN / -1`, which `wrapping_div` defines as `MIN`.
+    pub fn wrapping_div(self, other: VmValue) -> Result<VmValue, VmError> {
+        match (self, other) {
+            (VmValue::I32(val1), VmValue::I32(val2)) => {
+                if val2 == 0 {
+                    return Err(VmError::DivideByZero);
+                }
+                Ok(VmValue::I32(val1.wrapping_div(val2)))
+            }
+            (VmValue::I64(val1), VmValue::I64(val2)) => {
+                if val2 == 0 {
+                    return Err(VmError::DivideByZero);
+                }
+                Ok(VmValue::I64(val1.wrapping_div(val2)))
+            }
+            (VmValue::I128(val1), VmValue::I128(val2)) => {
+                if val2 == 0 {
+                    return Err(VmError::DivideByZero);
+                }
+                if val1 == i128::MIN && val2 == -1 {
+                    return Ok(VmValue::I128(i128::MIN));
+                }
+                let (quotient, _) = VmValue::sdivmod128(val1, val2)?;
+                Ok(VmValue::I128(quotient))
+            }
+            // Unsigned division never overflows, so `U128` is identical to
+            // `impl Div`'s checked result.
+            (VmValue::U128(_), VmValue::U128(_)) => self.div(other),
+            (_, _) => self.div(other),
         }
     }
 
-    fn check_f32_infinite(val: f32) -> Option<f32> {
-        if val.is_infinite() {
-            None
-        } else {
-            Some(val)
+    /// Division that saturates to the integer type's min/max on overflow
+    /// instead of erroring. See `wrapping_div` for the divide-by-zero and
+    /// `MIN / -1` notes, which apply identically here except that the
+    /// overflowing quotient saturates to `MAX` instead of wrapping to `MIN`.
+    pub fn saturating_div(self, other: VmValue) -> Result<VmValue, VmError> {
+        match (self, other) {
+            (VmValue::I32(val1), VmValue::I32(val2)) => {
+                if val2 == 0 {
+                    return Err(VmError::DivideByZero);
+                }
+                Ok(VmValue::I32(val1.saturating_div(val2)))
+            }
+            (VmValue::I64(val1), VmValue::I64(val2)) => {
+                if val2 == 0 {
+                    return Err(VmError::DivideByZero);
+                }
+                Ok(VmValue::I64(val1.saturating_div(val2)))
+            }
+            (VmValue::I128(val1), VmValue::I128(val2)) => {
+                if val2 == 0 {
+                    return Err(VmError::DivideByZero);
+                }
+                if val1 == i128::MIN && val2 == -1 {
+                    return Ok(VmValue::I128(i128::MAX));
+                }
+                let (quotient, _) = VmValue::sdivmod128(val1, val2)?;
+                Ok(VmValue::I128(quotient))
+            }
+            (VmValue::U128(_), VmValue::U128(_)) => self.div(other),
+            (_, _) => self.div(other),
         }
     }
 
-    fn check_f64_infinite(val: f64) -> Option<f64> {
-        if val.is_infinite() {
-            None
-        } else {
-            Some(val)
+    /// Remainder under `Wrapping`. A remainder's magnitude is always smaller
+    /// than the divisor, so the only case `checked_rem` treats as overflow
+    /// (`MIN % -1`) has nothing to wrap: it's defined as zero, matching
+    /// `wrapping_rem`.
+    pub fn wrapping_rem(self, other: VmValue) -> Result<VmValue, VmError> {
+        match (self, other) {
+            (VmValue::I32(val1), VmValue::I32(val2)) => {
+                if val2 == 0 {
+                    return Err(VmError::DivideByZero);
+                }
+                Ok(VmValue::I32(val1.wrapping_rem(val2)))
+            }
+            (VmValue::I64(val1), VmValue::I64(val2)) => {
+                if val2 == 0 {
+                    return Err(VmError::DivideByZero);
+                }
+                Ok(VmValue::I64(val1.wrapping_rem(val2)))
+            }
+            (VmValue::I128(val1), VmValue::I128(val2)) => {
+                if val2 == 0 {
+                    return Err(VmError::DivideByZero);
+                }
+                if val1 == i128::MIN && val2 == -1 {
+                    return Ok(VmValue::I128(0));
+                }
+                let (_, remainder) = VmValue::sdivmod128(val1, val2)?;
+                Ok(VmValue::I128(remainder))
+            }
+            (VmValue::U128(_), VmValue::U128(_)) => self.rem(other),
+            (_, _) => self.rem(other),
         }
     }
 
-    fn sum_f32(val1: &f32, val2: &f32) -> Option<f32> {
-        VmValue::check_f32_infinite(val1 + val2)
+    /// Remainder under `Saturating`. There is no sensible "saturated"
+    /// remainder to compute (see `wrapping_rem`), so this is identical to
+    /// `wrapping_rem`.
+    pub fn saturating_rem(self, other: VmValue) -> Result<VmValue, VmError> {
+        self.wrapping_rem(other)
     }
 
-    fn sum_f64(val1: &f64, val2: &f64) -> Option<f64> {
-        VmValue::check_f64_infinite(val1 + val2)
+    /// Full double-width product: `I32 * I32 -> I64` and `I64 * I64 -> I128`,
+    /// so a contract computing e.g. `a*b mod m` on near-max-width operands
+    /// doesn't have to catch a spurious `Overflow` from `checked_mul` before
+    /// it can even take the modulus. The array arms widen every lane the
+    /// same way (`i32ArrayN -> i64ArrayN`); there is no `i64ArrayN -> I128`
+    /// array variant to widen into, so `i64ArrayN` isn't accepted here.
+    pub fn mul_wide(self, other: VmValue) -> Result<VmValue, VmError> {
+        match (self, other) {
+            (VmValue::I32(val1), VmValue::I32(val2)) => {
+                Ok(VmValue::I64(val1 as i64 * val2 as i64))
+            }
+            (VmValue::I64(val1), VmValue::I64(val2)) => {
+                Ok(VmValue::I128(val1 as i128 * val2 as i128))
+            }
+            mul_wide_arm!(i32Array2, i64Array2, 2),
+            mul_wide_arm!(i32Array4, i64Array4, 4),
+            mul_wide_arm!(i32Array8, i64Array8, 8),
+            mul_wide_arm!(i32Array16, i64Array16, 16),
+            mul_wide_arm!(i32Array32, i64Array32, 32),
+            mul_wide_arm!(i32Array64, i64Array64, 64),
+            mul_wide_arm!(i32Array128, i64Array128, 128),
+            mul_wide_arm!(i32Array256, i64Array256, 256),
+            (_, _) => Err(VmError::TypeMismatch),
+        }
     }
 
-    fn sub_f32(val1: &f32, val2: &f32) -> Option<f32> {
-        VmValue::check_f32_infinite(val1 - val2)
+    /// Metered `Mul`: charges `fuel` proportionally to `self`'s lane count,
+    /// runs `ctx`'s trace hook, then falls through to `impl Mul`'s checked
+    /// behavior. See `primitives::fuel` for the gas/tracing model.
+    pub fn mul_metered(self, other: VmValue, ctx: &mut FuelContext) -> Result<VmValue, VmError> {
+        ctx.charge(self.lane_count())?;
+        ctx.trace("mul", self, other)?;
+        self.mul(other)
     }
 
-    fn sub_f64(val1: &f64, val2: &f64) -> Option<f64> {
-        VmValue::check_f64_infinite(val1 - val2)
+    /// Metered `Div`, see `mul_metered`.
+    pub fn div_metered(self, other: VmValue, ctx: &mut FuelContext) -> Result<VmValue, VmError> {
+        ctx.charge(self.lane_count())?;
+        ctx.trace("div", self, other)?;
+        self.div(other)
     }
 
-    fn mul_f32(val1: &f32, val2: &f32) -> Option<f32> {
-        VmValue::check_f32_infinite(val1 * val2)
+    /// Metered `Rem`, see `mul_metered`.
+    pub fn rem_metered(self, other: VmValue, ctx: &mut FuelContext) -> Result<VmValue, VmError> {
+        ctx.charge(self.lane_count())?;
+        ctx.trace("rem", self, other)?;
+        self.rem(other)
     }
 
-    fn mul_f64(val1: &f64, val2: &f64) -> Option<f64> {
-        VmValue::check_f64_infinite(val1 * val2)
+    /// Widens `F16`/`Bf16` (scalar or array) to `F32`. The array arms go
+    /// through `half`'s vectorized `to_f32_slice` so a whole tensor converts
+    /// in one pass instead of a per-element loop.
+    pub fn to_f32(&self) -> Result<VmValue, VmError> {
+        match *self {
+            VmValue::F16(val) => Ok(VmValue::F32(val.to_f32())),
+            VmValue::Bf16(val) => Ok(VmValue::F32(val.to_f32())),
+            widen_to_f32_arm!(f16Array2, f32Array2, 2),
+            widen_to_f32_arm!(f16Array4, f32Array4, 4),
+            widen_to_f32_arm!(f16Array8, f32Array8, 8),
+            widen_to_f32_arm!(f16Array16, f32Array16, 16),
+            widen_to_f32_arm!(f16Array32, f32Array32, 32),
+            widen_to_f32_arm!(f16Array64, f32Array64, 64),
+            widen_to_f32_arm!(f16Array128, f32Array128, 128),
+            widen_to_f32_arm!(f16Array256, f32Array256, 256),
+            widen_to_f32_arm!(bf16Array2, f32Array2, 2),
+            widen_to_f32_arm!(bf16Array4, f32Array4, 4),
+            widen_to_f32_arm!(bf16Array8, f32Array8, 8),
+            widen_to_f32_arm!(bf16Array16, f32Array16, 16),
+            widen_to_f32_arm!(bf16Array32, f32Array32, 32),
+            widen_to_f32_arm!(bf16Array64, f32Array64, 64),
+            widen_to_f32_arm!(bf16Array128, f32Array128, 128),
+            widen_to_f32_arm!(bf16Array256, f32Array256, 256),
+            _ => Err(VmError::TypeMismatch),
+        }
     }
 
-    fn div_f32(val1: &f32, val2: &f32) -> Option<f32> {
-        if *val2 == 0.0 {
-            panic!("Attempted to divide by zero!")
+    /// Narrows `F32` (scalar or array) to `F16`. Lossy: overflowing
+    /// magnitudes saturate to `+-inf` and subnormal results flush to zero,
+    /// per IEEE 754, rather than simply truncating the mantissa — `half`'s
+    /// `from_f32`/`from_f32_slice` already implement this correctly.
+    pub fn to_f16(&self) -> Result<VmValue, VmError> {
+        match *self {
+            VmValue::F32(val) => Ok(VmValue::F16(f16::from_f32(val))),
+            narrow_from_f32_arm!(f32Array2, f16Array2, f16, f16::ZERO, 2),
+            narrow_from_f32_arm!(f32Array4, f16Array4, f16, f16::ZERO, 4),
+            narrow_from_f32_arm!(f32Array8, f16Array8, f16, f16::ZERO, 8),
+            narrow_from_f32_arm!(f32Array16, f16Array16, f16, f16::ZERO, 16),
+            narrow_from_f32_arm!(f32Array32, f16Array32, f16, f16::ZERO, 32),
+            narrow_from_f32_arm!(f32Array64, f16Array64, f16, f16::ZERO, 64),
+            narrow_from_f32_arm!(f32Array128, f16Array128, f16, f16::ZERO, 128),
+            narrow_from_f32_arm!(f32Array256, f16Array256, f16, f16::ZERO, 256),
+            _ => Err(VmError::TypeMismatch),
         }
-        VmValue::check_f32_infinite(val1 / val2)
     }
 
-    fn div_f64(val1: &f64, val2: &f64) -> Option<f64> {
-        if *val2 == 0.0 {
-            panic!("Attempted to divide by zero!")
+    /// Narrows `F32` (scalar or array) to `Bf16`: round-to-nearest-even on
+    /// the low 16 mantissa bits, not a truncation, via `half`'s
+    /// `from_f32`/`from_f32_slice`.
+    pub fn to_bf16(&self) -> Result<VmValue, VmError> {
+        match *self {
+            VmValue::F32(val) => Ok(VmValue::Bf16(bf16::from_f32(val))),
+            narrow_from_f32_arm!(f32Array2, bf16Array2, bf16, bf16::ZERO, 2),
+            narrow_from_f32_arm!(f32Array4, bf16Array4, bf16, bf16::ZERO, 4),
+            narrow_from_f32_arm!(f32Array8, bf16Array8, bf16, bf16::ZERO, 8),
+            narrow_from_f32_arm!(f32Array16, bf16Array16, bf16, bf16::ZERO, 16),
+            narrow_from_f32_arm!(f32Array32, bf16Array32, bf16, bf16::ZERO, 32),
+            narrow_from_f32_arm!(f32Array64, bf16Array64, bf16, bf16::ZERO, 64),
+            narrow_from_f32_arm!(f32Array128, bf16Array128, bf16, bf16::ZERO, 128),
+            narrow_from_f32_arm!(f32Array256, bf16Array256, bf16, bf16::ZERO, 256),
+            _ => Err(VmError::TypeMismatch),
         }
-        VmValue::check_f64_infinite(val1 / val2)
     }
 
-    fn rem_f32(val1: &f32, val2: &f32) -> Option<f32> {
-        if *val2 == 0.0 {
-            panic!("Attempted to divide by zero!")
+    /// Cyclically shifts every lane of an `i32Array`/`i64Array`/`f32Array`/
+    /// `f64Array` variant left by `n` positions in place (see
+    /// `rotate_left_in_place`). `n` may be negative or larger than the lane
+    /// count; both wrap via `normalize_rotation`. A no-op (`Ok` of the
+    /// unchanged value) when `n` normalizes to `0`.
+    pub fn rotate_left(self, n: i64) -> Result<VmValue, VmError> {
+        match self {
+            rotate_left_arm!(i32Array2),
+            rotate_left_arm!(i32Array4),
+            rotate_left_arm!(i32Array8),
+            rotate_left_arm!(i32Array16),
+            rotate_left_arm!(i32Array32),
+            rotate_left_arm!(i32Array64),
+            rotate_left_arm!(i32Array128),
+            rotate_left_arm!(i32Array256),
+            rotate_left_arm!(i64Array2),
+            rotate_left_arm!(i64Array4),
+            rotate_left_arm!(i64Array8),
+            rotate_left_arm!(i64Array16),
+            rotate_left_arm!(i64Array32),
+            rotate_left_arm!(i64Array64),
+            rotate_left_arm!(i64Array128),
+            rotate_left_arm!(i64Array256),
+            rotate_left_arm!(f32Array2),
+            rotate_left_arm!(f32Array4),
+            rotate_left_arm!(f32Array8),
+            rotate_left_arm!(f32Array16),
+            rotate_left_arm!(f32Array32),
+            rotate_left_arm!(f32Array64),
+            rotate_left_arm!(f32Array128),
+            rotate_left_arm!(f32Array256),
+            rotate_left_arm!(f64Array2),
+            rotate_left_arm!(f64Array4),
+            rotate_left_arm!(f64Array8),
+            rotate_left_arm!(f64Array16),
+            rotate_left_arm!(f64Array32),
+            rotate_left_arm!(f64Array64),
+            rotate_left_arm!(f64Array128),
+            rotate_left_arm!(f64Array256),
+            _ => Err(VmError::TypeMismatch),
         }
-        VmValue::check_f32_infinite(val1 % val2)
     }
 
-    fn rem_f64(val1: &f64, val2: &f64) -> Option<f64> {
-        if *val2 == 0.0 {
-            panic!("Attempted to divide by zero!")
+    /// `rotate_left`'s mirror image: a right-rotation by `n` is the same
+    /// lane permutation as a left-rotation by `-n`, so this just negates `n`
+    /// (via `wrapping_neg`, since `n == i64::MIN` has no ordinary negation)
+    /// and delegates; `normalize_rotation` handles the resulting value the
+    /// same way it handles any other out-of-range shift.
+    pub fn rotate_right(self, n: i64) -> Result<VmValue, VmError> {
+        self.rotate_left(n.wrapping_neg())
+    }
+
+    /// Byte-exact, platform-independent encoding for consensus hashing: a
+    /// single discriminant byte (see the `TAG_*` constants above) followed by
+    /// the value's little-endian element encodings, with no length prefix
+    /// since the tag alone implies the fixed array width. Every `F32`/`F64`/
+    /// `F16`/`Bf16` lane is canonicalized first (`canonical_f32` et al.) so
+    /// that two hardware-divergent NaN payloads, or `+0.0` vs. `-0.0`, always
+    /// hash identically. Round-trips through `from_canonical_bytes`.
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        match *self {
+            VmValue::I32(val) => {
+                let mut bytes = vec![TAG_I32];
+                bytes.extend_from_slice(&val.to_le_bytes());
+                bytes
+            }
+            VmValue::I64(val) => {
+                let mut bytes = vec![TAG_I64];
+                bytes.extend_from_slice(&val.to_le_bytes());
+                bytes
+            }
+            VmValue::I128(val) => {
+                let mut bytes = vec![TAG_I128];
+                bytes.extend_from_slice(&val.to_le_bytes());
+                bytes
+            }
+            VmValue::U128(val) => {
+                let mut bytes = vec![TAG_U128];
+                bytes.extend_from_slice(&val.to_le_bytes());
+                bytes
+            }
+            VmValue::F32(val) => {
+                let mut bytes = vec![TAG_F32];
+                bytes.extend_from_slice(&canonical_f32(val).to_le_bytes());
+                bytes
+            }
+            VmValue::F64(val) => {
+                let mut bytes = vec![TAG_F64];
+                bytes.extend_from_slice(&canonical_f64(val).to_le_bytes());
+                bytes
+            }
+            VmValue::F16(val) => {
+                let mut bytes = vec![TAG_F16];
+                bytes.extend_from_slice(&canonical_f16(val).to_bits().to_le_bytes());
+                bytes
+            }
+            VmValue::Bf16(val) => {
+                let mut bytes = vec![TAG_BF16];
+                bytes.extend_from_slice(&canonical_bf16(val).to_bits().to_le_bytes());
+                bytes
+            }
+            VmValue::Fe25519(val) => {
+                let mut bytes = vec![TAG_FE25519];
+                for limb in fe25519::freeze(&val).iter() {
+                    bytes.extend_from_slice(&limb.to_le_bytes());
+                }
+                bytes
+            }
+            int_array_bytes_arm!(i32Array2, TAG_I32_ARRAY2),
+            int_array_bytes_arm!(i32Array4, TAG_I32_ARRAY4),
+            int_array_bytes_arm!(i32Array8, TAG_I32_ARRAY8),
+            int_array_bytes_arm!(i32Array16, TAG_I32_ARRAY16),
+            int_array_bytes_arm!(i32Array32, TAG_I32_ARRAY32),
+            int_array_bytes_arm!(i32Array64, TAG_I32_ARRAY64),
+            int_array_bytes_arm!(i32Array128, TAG_I32_ARRAY128),
+            int_array_bytes_arm!(i32Array256, TAG_I32_ARRAY256),
+            int_array_bytes_arm!(i64Array2, TAG_I64_ARRAY2),
+            int_array_bytes_arm!(i64Array4, TAG_I64_ARRAY4),
+            int_array_bytes_arm!(i64Array8, TAG_I64_ARRAY8),
+            int_array_bytes_arm!(i64Array16, TAG_I64_ARRAY16),
+            int_array_bytes_arm!(i64Array32, TAG_I64_ARRAY32),
+            int_array_bytes_arm!(i64Array64, TAG_I64_ARRAY64),
+            int_array_bytes_arm!(i64Array128, TAG_I64_ARRAY128),
+            int_array_bytes_arm!(i64Array256, TAG_I64_ARRAY256),
+            float_array_bytes_arm!(f32Array2, TAG_F32_ARRAY2, canonical_f32),
+            float_array_bytes_arm!(f32Array4, TAG_F32_ARRAY4, canonical_f32),
+            float_array_bytes_arm!(f32Array8, TAG_F32_ARRAY8, canonical_f32),
+            float_array_bytes_arm!(f32Array16, TAG_F32_ARRAY16, canonical_f32),
+            float_array_bytes_arm!(f32Array32, TAG_F32_ARRAY32, canonical_f32),
+            float_array_bytes_arm!(f32Array64, TAG_F32_ARRAY64, canonical_f32),
+            float_array_bytes_arm!(f32Array128, TAG_F32_ARRAY128, canonical_f32),
+            float_array_bytes_arm!(f32Array256, TAG_F32_ARRAY256, canonical_f32),
+            float_array_bytes_arm!(f64Array2, TAG_F64_ARRAY2, canonical_f64),
+            float_array_bytes_arm!(f64Array4, TAG_F64_ARRAY4, canonical_f64),
+            float_array_bytes_arm!(f64Array8, TAG_F64_ARRAY8, canonical_f64),
+            float_array_bytes_arm!(f64Array16, TAG_F64_ARRAY16, canonical_f64),
+            float_array_bytes_arm!(f64Array32, TAG_F64_ARRAY32, canonical_f64),
+            float_array_bytes_arm!(f64Array64, TAG_F64_ARRAY64, canonical_f64),
+            float_array_bytes_arm!(f64Array128, TAG_F64_ARRAY128, canonical_f64),
+            float_array_bytes_arm!(f64Array256, TAG_F64_ARRAY256, canonical_f64),
+            half_array_bytes_arm!(f16Array2, TAG_F16_ARRAY2, canonical_f16),
+            half_array_bytes_arm!(f16Array4, TAG_F16_ARRAY4, canonical_f16),
+            half_array_bytes_arm!(f16Array8, TAG_F16_ARRAY8, canonical_f16),
+            half_array_bytes_arm!(f16Array16, TAG_F16_ARRAY16, canonical_f16),
+            half_array_bytes_arm!(f16Array32, TAG_F16_ARRAY32, canonical_f16),
+            half_array_bytes_arm!(f16Array64, TAG_F16_ARRAY64, canonical_f16),
+            half_array_bytes_arm!(f16Array128, TAG_F16_ARRAY128, canonical_f16),
+            half_array_bytes_arm!(f16Array256, TAG_F16_ARRAY256, canonical_f16),
+            half_array_bytes_arm!(bf16Array2, TAG_BF16_ARRAY2, canonical_bf16),
+            half_array_bytes_arm!(bf16Array4, TAG_BF16_ARRAY4, canonical_bf16),
+            half_array_bytes_arm!(bf16Array8, TAG_BF16_ARRAY8, canonical_bf16),
+            half_array_bytes_arm!(bf16Array16, TAG_BF16_ARRAY16, canonical_bf16),
+            half_array_bytes_arm!(bf16Array32, TAG_BF16_ARRAY32, canonical_bf16),
+            half_array_bytes_arm!(bf16Array64, TAG_BF16_ARRAY64, canonical_bf16),
+            half_array_bytes_arm!(bf16Array128, TAG_BF16_ARRAY128, canonical_bf16),
+            half_array_bytes_arm!(bf16Array256, TAG_BF16_ARRAY256, canonical_bf16),
+        }
+    }
+
+    /// Inverse of `to_canonical_bytes`: given the discriminant byte split off
+    /// the front of the encoding and the remaining payload, reconstructs the
+    /// `VmValue`. Returns `VmError::TypeMismatch` for an unknown tag or a
+    /// payload shorter than the tag's fixed width.
+    pub fn from_canonical_bytes(tag: u8, bytes: &[u8]) -> Result<VmValue, VmError> {
+        match tag {
+            TAG_I32 => {
+                let buf: [u8; 4] = bytes.get(0..4).ok_or(VmError::TypeMismatch)?.try_into().unwrap();
+                Ok(VmValue::I32(i32::from_le_bytes(buf)))
+            }
+            TAG_I64 => {
+                let buf: [u8; 8] = bytes.get(0..8).ok_or(VmError::TypeMismatch)?.try_into().unwrap();
+                Ok(VmValue::I64(i64::from_le_bytes(buf)))
+            }
+            TAG_I128 => {
+                let buf: [u8; 16] = bytes.get(0..16).ok_or(VmError::TypeMismatch)?.try_into().unwrap();
+                Ok(VmValue::I128(i128::from_le_bytes(buf)))
+            }
+            TAG_U128 => {
+                let buf: [u8; 16] = bytes.get(0..16).ok_or(VmError::TypeMismatch)?.try_into().unwrap();
+                Ok(VmValue::U128(u128::from_le_bytes(buf)))
+            }
+            TAG_F32 => {
+                let buf: [u8; 4] = bytes.get(0..4).ok_or(VmError::TypeMismatch)?.try_into().unwrap();
+                Ok(VmValue::F32(f32::from_le_bytes(buf)))
+            }
+            TAG_F64 => {
+                let buf: [u8; 8] = bytes.get(0..8).ok_or(VmError::TypeMismatch)?.try_into().unwrap();
+                Ok(VmValue::F64(f64::from_le_bytes(buf)))
+            }
+            TAG_F16 => {
+                let buf: [u8; 2] = bytes.get(0..2).ok_or(VmError::TypeMismatch)?.try_into().unwrap();
+                Ok(VmValue::F16(f16::from_bits(u16::from_le_bytes(buf))))
+            }
+            TAG_BF16 => {
+                let buf: [u8; 2] = bytes.get(0..2).ok_or(VmError::TypeMismatch)?.try_into().unwrap();
+                Ok(VmValue::Bf16(bf16::from_bits(u16::from_le_bytes(buf))))
+            }
+            TAG_FE25519 => {
+                let mut limbs: [u64; 5] = [0; 5];
+                for (i, limb) in limbs.iter_mut().enumerate() {
+                    let start = i * 8;
+                    let buf: [u8; 8] = bytes
+                        .get(start..start + 8)
+                        .ok_or(VmError::TypeMismatch)?
+                        .try_into()
+                        .unwrap();
+                    *limb = u64::from_le_bytes(buf);
+                }
+                Ok(VmValue::Fe25519(limbs))
+            }
+            fixed_array_from_bytes_arm!(TAG_I32_ARRAY2, i32Array2, i32, 4, 2),
+            fixed_array_from_bytes_arm!(TAG_I32_ARRAY4, i32Array4, i32, 4, 4),
+            fixed_array_from_bytes_arm!(TAG_I32_ARRAY8, i32Array8, i32, 4, 8),
+            fixed_array_from_bytes_arm!(TAG_I32_ARRAY16, i32Array16, i32, 4, 16),
+            fixed_array_from_bytes_arm!(TAG_I32_ARRAY32, i32Array32, i32, 4, 32),
+            fixed_array_from_bytes_arm!(TAG_I32_ARRAY64, i32Array64, i32, 4, 64),
+            fixed_array_from_bytes_arm!(TAG_I32_ARRAY128, i32Array128, i32, 4, 128),
+            fixed_array_from_bytes_arm!(TAG_I32_ARRAY256, i32Array256, i32, 4, 256),
+            fixed_array_from_bytes_arm!(TAG_I64_ARRAY2, i64Array2, i64, 8, 2),
+            fixed_array_from_bytes_arm!(TAG_I64_ARRAY4, i64Array4, i64, 8, 4),
+            fixed_array_from_bytes_arm!(TAG_I64_ARRAY8, i64Array8, i64, 8, 8),
+            fixed_array_from_bytes_arm!(TAG_I64_ARRAY16, i64Array16, i64, 8, 16),
+            fixed_array_from_bytes_arm!(TAG_I64_ARRAY32, i64Array32, i64, 8, 32),
+            fixed_array_from_bytes_arm!(TAG_I64_ARRAY64, i64Array64, i64, 8, 64),
+            fixed_array_from_bytes_arm!(TAG_I64_ARRAY128, i64Array128, i64, 8, 128),
+            fixed_array_from_bytes_arm!(TAG_I64_ARRAY256, i64Array256, i64, 8, 256),
+            fixed_array_from_bytes_arm!(TAG_F32_ARRAY2, f32Array2, f32, 4, 2),
+            fixed_array_from_bytes_arm!(TAG_F32_ARRAY4, f32Array4, f32, 4, 4),
+            fixed_array_from_bytes_arm!(TAG_F32_ARRAY8, f32Array8, f32, 4, 8),
+            fixed_array_from_bytes_arm!(TAG_F32_ARRAY16, f32Array16, f32, 4, 16),
+            fixed_array_from_bytes_arm!(TAG_F32_ARRAY32, f32Array32, f32, 4, 32),
+            fixed_array_from_bytes_arm!(TAG_F32_ARRAY64, f32Array64, f32, 4, 64),
+            fixed_array_from_bytes_arm!(TAG_F32_ARRAY128, f32Array128, f32, 4, 128),
+            fixed_array_from_bytes_arm!(TAG_F32_ARRAY256, f32Array256, f32, 4, 256),
+            fixed_array_from_bytes_arm!(TAG_F64_ARRAY2, f64Array2, f64, 8, 2),
+            fixed_array_from_bytes_arm!(TAG_F64_ARRAY4, f64Array4, f64, 8, 4),
+            fixed_array_from_bytes_arm!(TAG_F64_ARRAY8, f64Array8, f64, 8, 8),
+            fixed_array_from_bytes_arm!(TAG_F64_ARRAY16, f64Array16, f64, 8, 16),
+            fixed_array_from_bytes_arm!(TAG_F64_ARRAY32, f64Array32, f64, 8, 32),
+            fixed_array_from_bytes_arm!(TAG_F64_ARRAY64, f64Array64, f64, 8, 64),
+            fixed_array_from_bytes_arm!(TAG_F64_ARRAY128, f64Array128, f64, 8, 128),
+            fixed_array_from_bytes_arm!(TAG_F64_ARRAY256, f64Array256, f64, 8, 256),
+            half_array_from_bytes_arm!(TAG_F16_ARRAY2, f16Array2, f16, 2),
+            half_array_from_bytes_arm!(TAG_F16_ARRAY4, f16Array4, f16, 4),
+            half_array_from_bytes_arm!(TAG_F16_ARRAY8, f16Array8, f16, 8),
+            half_array_from_bytes_arm!(TAG_F16_ARRAY16, f16Array16, f16, 16),
+            half_array_from_bytes_arm!(TAG_F16_ARRAY32, f16Array32, f16, 32),
+            half_array_from_bytes_arm!(TAG_F16_ARRAY64, f16Array64, f16, 64),
+            half_array_from_bytes_arm!(TAG_F16_ARRAY128, f16Array128, f16, 128),
+            half_array_from_bytes_arm!(TAG_F16_ARRAY256, f16Array256, f16, 256),
+            half_array_from_bytes_arm!(TAG_BF16_ARRAY2, bf16Array2, bf16, 2),
+            half_array_from_bytes_arm!(TAG_BF16_ARRAY4, bf16Array4, bf16, 4),
+            half_array_from_bytes_arm!(TAG_BF16_ARRAY8, bf16Array8, bf16, 8),
+            half_array_from_bytes_arm!(TAG_BF16_ARRAY16, bf16Array16, bf16, 16),
+            half_array_from_bytes_arm!(TAG_BF16_ARRAY32, bf16Array32, bf16, 32),
+            half_array_from_bytes_arm!(TAG_BF16_ARRAY64, bf16Array64, bf16, 64),
+            half_array_from_bytes_arm!(TAG_BF16_ARRAY128, bf16Array128, bf16, 128),
+            half_array_from_bytes_arm!(TAG_BF16_ARRAY256, bf16Array256, bf16, 256),
+            _ => Err(VmError::TypeMismatch),
         }
-        VmValue::check_f64_infinite(val1 % val2)
     }
 }
 
@@ -182,21 +2094,65 @@ impl PartialEq for VmValue {
         match (*self, *other) {
             (VmValue::I32(val1), VmValue::I32(val2)) => val1 == val2,
             (VmValue::I64(val1), VmValue::I64(val2)) => val1 == val2,
+            (VmValue::I128(val1), VmValue::I128(val2)) => val1 == val2,
+            (VmValue::U128(val1), VmValue::U128(val2)) => val1 == val2,
             (VmValue::F32(val1), VmValue::F32(val2)) => val1 == val2,
             (VmValue::F64(val1), VmValue::F64(val2)) => val1 == val2,
-            (VmValue::i32Array2(val1), VmValue::i32Array2(val2)) => val1 == val2,
-            (VmValue::i32Array4(val1), VmValue::i32Array4(val2)) => val1 == val2,
-            (VmValue::i32Array8(val1), VmValue::i32Array8(val2)) => val1 == val2,
-            (VmValue::i64Array2(val1), VmValue::i64Array2(val2)) => val1 == val2,
-            (VmValue::i64Array4(val1), VmValue::i64Array4(val2)) => val1 == val2,
-            (VmValue::i64Array8(val1), VmValue::i64Array8(val2)) => val1 == val2,
-            (VmValue::f32Array2(val1), VmValue::f32Array2(val2)) => val1 == val2,
-            (VmValue::f32Array4(val1), VmValue::f32Array4(val2)) => val1 == val2,
-            (VmValue::f32Array8(val1), VmValue::f32Array8(val2)) => val1 == val2,
-            (VmValue::f64Array2(val1), VmValue::f64Array2(val2)) => val1 == val2,
-            (VmValue::f64Array4(val1), VmValue::f64Array4(val2)) => val1 == val2,
-            (VmValue::f64Array8(val1), VmValue::f64Array8(val2)) => val1 == val2,
-            (_, _) => panic!("Cannot perform equality between different variants!"),
+            (VmValue::F16(val1), VmValue::F16(val2)) => val1 == val2,
+            (VmValue::Bf16(val1), VmValue::Bf16(val2)) => val1 == val2,
+            (VmValue::Fe25519(val1), VmValue::Fe25519(val2)) => fe25519::eq(&val1, &val2),
+            eq_direct_arm!(i32Array2),
+            eq_direct_arm!(i32Array4),
+            eq_direct_arm!(i32Array8),
+            eq_direct_arm!(i32Array16),
+            eq_direct_arm!(i32Array32),
+            eq_iter_arm!(i32Array64),
+            eq_iter_arm!(i32Array128),
+            eq_iter_arm!(i32Array256),
+            eq_direct_arm!(i64Array2),
+            eq_direct_arm!(i64Array4),
+            eq_direct_arm!(i64Array8),
+            eq_direct_arm!(i64Array16),
+            eq_direct_arm!(i64Array32),
+            eq_iter_arm!(i64Array64),
+            eq_iter_arm!(i64Array128),
+            eq_iter_arm!(i64Array256),
+            eq_direct_arm!(f32Array2),
+            eq_direct_arm!(f32Array4),
+            eq_direct_arm!(f32Array8),
+            eq_direct_arm!(f32Array16),
+            eq_direct_arm!(f32Array32),
+            eq_iter_arm!(f32Array64),
+            eq_iter_arm!(f32Array128),
+            eq_iter_arm!(f32Array256),
+            eq_direct_arm!(f64Array2),
+            eq_direct_arm!(f64Array4),
+            eq_direct_arm!(f64Array8),
+            eq_direct_arm!(f64Array16),
+            eq_direct_arm!(f64Array32),
+            eq_iter_arm!(f64Array64),
+            eq_iter_arm!(f64Array128),
+            eq_iter_arm!(f64Array256),
+            eq_direct_arm!(f16Array2),
+            eq_direct_arm!(f16Array4),
+            eq_direct_arm!(f16Array8),
+            eq_direct_arm!(f16Array16),
+            eq_direct_arm!(f16Array32),
+            eq_iter_arm!(f16Array64),
+            eq_iter_arm!(f16Array128),
+            eq_iter_arm!(f16Array256),
+            eq_direct_arm!(bf16Array2),
+            eq_direct_arm!(bf16Array4),
+            eq_direct_arm!(bf16Array8),
+            eq_direct_arm!(bf16Array16),
+            eq_direct_arm!(bf16Array32),
+            eq_iter_arm!(bf16Array64),
+            eq_iter_arm!(bf16Array128),
+            eq_iter_arm!(bf16Array256),
+            // Mismatched variants are simply unequal rather than a panic: an
+            // attacker-supplied contract can compare any two `VmValue`s it
+            // can construct, and this op must never unwind the host.
+            (_, _) => false,
         }
     }
 }
@@ -222,7 +2178,7 @@ impl PartialOrd for VmValue {
                     Some(Ordering::Equal)
                 }
             }
-            (VmValue::F32(val1), VmValue::F32(val2)) => {
+            (VmValue::I128(val1), VmValue::I128(val2)) => {
                 if val1 < val2 {
                     Some(Ordering::Less)
                 } else if val1 > val2 {
@@ -231,7 +2187,7 @@ impl PartialOrd for VmValue {
                     Some(Ordering::Equal)
                 }
             }
-            (VmValue::F64(val1), VmValue::F64(val2)) => {
+            (VmValue::U128(val1), VmValue::U128(val2)) => {
                 if val1 < val2 {
                     Some(Ordering::Less)
                 } else if val1 > val2 {
@@ -240,43 +2196,59 @@ impl PartialOrd for VmValue {
                     Some(Ordering::Equal)
                 }
             }
-            (VmValue::i32Array2(val1), VmValue::i32Array2(val2)) => {
-                panic!("Cannot perform comparison between arrays!")
-            }
-            (VmValue::i32Array4(val1), VmValue::i32Array4(val2)) => {
-                panic!("Cannot perform comparison between arrays!")
-            }
-            (VmValue::i32Array8(val1), VmValue::i32Array8(val2)) => {
-                panic!("Cannot perform comparison between arrays!")
-            }
-            (VmValue::i64Array2(val1), VmValue::i64Array2(val2)) => {
-                panic!("Cannot perform comparison between arrays!")
-            }
-            (VmValue::i64Array4(val1), VmValue::i64Array4(val2)) => {
-                panic!("Cannot perform comparison between arrays!")
-            }
-            (VmValue::i64Array8(val1), VmValue::i64Array8(val2)) => {
-                panic!("Cannot perform comparison between arrays!")
-            }
-            (VmValue::f32Array2(val1), VmValue::f32Array2(val2)) => {
-                panic!("Cannot perform comparison between arrays!")
-            }
-            (VmValue::f32Array4(val1), VmValue::f32Array4(val2)) => {
-                panic!("Cannot perform comparison between arrays!")
-            }
-            (VmValue::f32Array8(val1), VmValue::f32Array8(val2)) => {
-                panic!("Cannot perform comparison between arrays!")
+            (VmValue::F32(val1), VmValue::F32(val2)) => {
+                if val1 < val2 {
+                    Some(Ordering::Less)
+                } else if val1 > val2 {
+                    Some(Ordering::Greater)
+                } else {
+                    Some(Ordering::Equal)
+                }
             }
-            (VmValue::f64Array2(val1), VmValue::f64Array2(val2)) => {
-                panic!("Cannot perform comparison between arrays!")
+            (VmValue::F64(val1), VmValue::F64(val2)) => {
+                if val1 < val2 {
+                    Some(Ordering::Less)
+                } else if val1 > val2 {
+                    Some(Ordering::Greater)
+                } else {
+                    Some(Ordering::Equal)
+                }
             }
-            (VmValue::f64Array4(val1), VmValue::f64Array4(val2)) => {
-                panic!("Cannot perform comparison between arrays!")
+            (VmValue::F16(val1), VmValue::F16(val2)) => {
+                if val1 < val2 {
+                    Some(Ordering::Less)
+                } else if val1 > val2 {
+                    Some(Ordering::Greater)
+                } else {
+                    Some(Ordering::Equal)
+                }
             }
-            (VmValue::f64Array8(val1), VmValue::f64Array8(val2)) => {
-                panic!("Cannot perform comparison between arrays!")
+            (VmValue::Bf16(val1), VmValue::Bf16(val2)) => {
+                if val1 < val2 {
+                    Some(Ordering::Less)
+                } else if val1 > val2 {
+                    Some(Ordering::Greater)
+                } else {
+                    Some(Ordering::Equal)
+                }
             }
-            (_, _) => panic!("Cannot perform compare between different variants!"),
+            // Arrays have no total order; a mismatched-variant pair is
+            // likewise incomparable. `None` is the idiomatic `PartialOrd`
+            // answer for "incomparable" and, unlike `eq`'s all-or-nothing
+            // bool, costs nothing to express correctly.
+            (VmValue::i32Array2(_), VmValue::i32Array2(_))
+            | (VmValue::i32Array4(_), VmValue::i32Array4(_))
+            | (VmValue::i32Array8(_), VmValue::i32Array8(_))
+            | (VmValue::i64Array2(_), VmValue::i64Array2(_))
+            | (VmValue::i64Array4(_), VmValue::i64Array4(_))
+            | (VmValue::i64Array8(_), VmValue::i64Array8(_))
+            | (VmValue::f32Array2(_), VmValue::f32Array2(_))
+            | (VmValue::f32Array4(_), VmValue::f32Array4(_))
+            | (VmValue::f32Array8(_), VmValue::f32Array8(_))
+            | (VmValue::f64Array2(_), VmValue::f64Array2(_))
+            | (VmValue::f64Array4(_), VmValue::f64Array4(_))
+            | (VmValue::f64Array8(_), VmValue::f64Array8(_)) => None,
+            (_, _) => None,
         }
     }
 }
@@ -284,7 +2256,10 @@ impl PartialOrd for VmValue {
 impl Add for VmValue {
     type Output = Result<VmValue, VmError>;
 
-    // TODO: Possibly use native SIMD for arrays, but benchmark first
+    // Integer array lanes route through `simd_arith`, which takes the vectorized
+    // path under the `simd` feature (see `benches/value_arith.rs` for the
+    // scalar-vs-SIMD comparison) and the scalar loop otherwise; float array
+    // lanes go through `float_arith_arm!`'s per-lane loop.
     fn add(self, other: VmValue) -> Result<VmValue, VmError> {
         match (self, other) {
             (VmValue::I32(val1), VmValue::I32(val2)) => match val1.checked_add(val2) {
@@ -295,6 +2270,14 @@ impl Add for VmValue {
                 Some(result) => Ok(VmValue::I64(result)),
                 None => Err(VmError::Overflow),
             },
+            (VmValue::I128(val1), VmValue::I128(val2)) => match val1.checked_add(val2) {
+                Some(result) => Ok(VmValue::I128(result)),
+                None => Err(VmError::Overflow),
+            },
+            (VmValue::U128(val1), VmValue::U128(val2)) => match val1.checked_add(val2) {
+                Some(result) => Ok(VmValue::U128(result)),
+                None => Err(VmError::Overflow),
+            },
             (VmValue::F32(val1), VmValue::F32(val2)) => match VmValue::sum_f32(&val1, &val2) {
                 Some(result) => Ok(VmValue::F32(result)),
                 None => Err(VmError::Infinity),
@@ -303,164 +2286,42 @@ impl Add for VmValue {
                 Some(result) => Ok(VmValue::F64(result)),
                 None => Err(VmError::Infinity),
             },
-            (VmValue::i32Array2(val1), VmValue::i32Array2(val2)) => {
-                let mut result: [i32; 2] = [0; 2];
-
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_add(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array2(result))
-            }
-            (VmValue::i32Array4(val1), VmValue::i32Array4(val2)) => {
-                let mut result: [i32; 4] = [0; 4];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_add(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array4(result))
-            }
-            (VmValue::i32Array8(val1), VmValue::i32Array8(val2)) => {
-                let mut result: [i32; 8] = [0; 8];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_add(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array8(result))
-            }
-            (VmValue::i64Array2(val1), VmValue::i64Array2(val2)) => {
-                let mut result: [i64; 2] = [0; 2];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_add(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array2(result))
-            }
-            (VmValue::i64Array4(val1), VmValue::i64Array4(val2)) => {
-                let mut result: [i64; 4] = [0; 4];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_add(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array4(result))
-            }
-            (VmValue::i64Array8(val1), VmValue::i64Array8(val2)) => {
-                let mut result: [i64; 8] = [0; 8];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_add(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array8(result))
-            }
-            (VmValue::f32Array2(val1), VmValue::f32Array2(val2)) => {
-                let mut result: [f32; 2] = [0.0; 2];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::sum_f32(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array2(result))
-            }
-            (VmValue::f32Array4(val1), VmValue::f32Array4(val2)) => {
-                let mut result: [f32; 4] = [0.0; 4];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::sum_f32(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array4(result))
-            }
-            (VmValue::f32Array8(val1), VmValue::f32Array8(val2)) => {
-                let mut result: [f32; 8] = [0.0; 8];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::sum_f32(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array8(result))
-            }
-            (VmValue::f64Array2(val1), VmValue::f64Array2(val2)) => {
-                let mut result: [f64; 2] = [0.0; 2];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::sum_f64(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array2(result))
-            }
-            (VmValue::f64Array4(val1), VmValue::f64Array4(val2)) => {
-                let mut result: [f64; 4] = [0.0; 4];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::sum_f64(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array4(result))
-            }
-            (VmValue::f64Array8(val1), VmValue::f64Array8(val2)) => {
-                let mut result: [f64; 8] = [0.0; 8];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::sum_f64(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array8(result))
-            }
-            (_, _) => panic!("Cannot perform addition between different variants!"),
+            (VmValue::Fe25519(val1), VmValue::Fe25519(val2)) => {
+                Ok(VmValue::Fe25519(fe25519::add(&val1, &val2)))
+            }
+            int_arith_arm!(i32Array2, i32, 2, add_i32),
+            int_arith_arm!(i32Array4, i32, 4, add_i32),
+            int_arith_arm!(i32Array8, i32, 8, add_i32),
+            int_arith_arm!(i32Array16, i32, 16, add_i32),
+            int_arith_arm!(i32Array32, i32, 32, add_i32),
+            int_arith_arm!(i32Array64, i32, 64, add_i32),
+            int_arith_arm!(i32Array128, i32, 128, add_i32),
+            int_arith_arm!(i32Array256, i32, 256, add_i32),
+            int_arith_arm!(i64Array2, i64, 2, add_i64),
+            int_arith_arm!(i64Array4, i64, 4, add_i64),
+            int_arith_arm!(i64Array8, i64, 8, add_i64),
+            int_arith_arm!(i64Array16, i64, 16, add_i64),
+            int_arith_arm!(i64Array32, i64, 32, add_i64),
+            int_arith_arm!(i64Array64, i64, 64, add_i64),
+            int_arith_arm!(i64Array128, i64, 128, add_i64),
+            int_arith_arm!(i64Array256, i64, 256, add_i64),
+            float_arith_arm!(f32Array2, f32, 2, sum_f32),
+            float_arith_arm!(f32Array4, f32, 4, sum_f32),
+            float_arith_arm!(f32Array8, f32, 8, sum_f32),
+            float_arith_arm!(f32Array16, f32, 16, sum_f32),
+            float_arith_arm!(f32Array32, f32, 32, sum_f32),
+            float_arith_arm!(f32Array64, f32, 64, sum_f32),
+            float_arith_arm!(f32Array128, f32, 128, sum_f32),
+            float_arith_arm!(f32Array256, f32, 256, sum_f32),
+            float_arith_arm!(f64Array2, f64, 2, sum_f64),
+            float_arith_arm!(f64Array4, f64, 4, sum_f64),
+            float_arith_arm!(f64Array8, f64, 8, sum_f64),
+            float_arith_arm!(f64Array16, f64, 16, sum_f64),
+            float_arith_arm!(f64Array32, f64, 32, sum_f64),
+            float_arith_arm!(f64Array64, f64, 64, sum_f64),
+            float_arith_arm!(f64Array128, f64, 128, sum_f64),
+            float_arith_arm!(f64Array256, f64, 256, sum_f64),
+            (_, _) => Err(VmError::TypeMismatch),
         }
     }
 }
@@ -478,171 +2339,58 @@ impl Sub for VmValue {
                 Some(result) => Ok(VmValue::I64(result)),
                 None => Err(VmError::Overflow),
             },
-            (VmValue::F32(val1), VmValue::F32(val2)) => match VmValue::sub_f32(&val1, &val2) {
-                Some(result) => Ok(VmValue::F32(result)),
-                None => Err(VmError::Infinity),
+            (VmValue::I128(val1), VmValue::I128(val2)) => match val1.checked_sub(val2) {
+                Some(result) => Ok(VmValue::I128(result)),
+                None => Err(VmError::Overflow),
             },
-            (VmValue::F64(val1), VmValue::F64(val2)) => match VmValue::sub_f64(&val1, &val2) {
-                Some(result) => Ok(VmValue::F64(result)),
-                None => Err(VmError::Infinity),
+            (VmValue::U128(val1), VmValue::U128(val2)) => match val1.checked_sub(val2) {
+                Some(result) => Ok(VmValue::U128(result)),
+                None => Err(VmError::Overflow),
             },
-            (VmValue::i32Array2(val1), VmValue::i32Array2(val2)) => {
-                let mut result: [i32; 2] = [0; 2];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_sub(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array2(result))
-            }
-            (VmValue::i32Array4(val1), VmValue::i32Array4(val2)) => {
-                let mut result: [i32; 4] = [0; 4];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_sub(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array4(result))
-            }
-            (VmValue::i32Array8(val1), VmValue::i32Array8(val2)) => {
-                let mut result: [i32; 8] = [0; 8];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_sub(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array8(result))
-            }
-            (VmValue::i64Array2(val1), VmValue::i64Array2(val2)) => {
-                let mut result: [i64; 2] = [0; 2];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_sub(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array2(result))
-            }
-            (VmValue::i64Array4(val1), VmValue::i64Array4(val2)) => {
-                let mut result: [i64; 4] = [0; 4];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_sub(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array4(result))
-            }
-            (VmValue::i64Array8(val1), VmValue::i64Array8(val2)) => {
-                let mut result: [i64; 8] = [0; 8];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_sub(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array8(result))
-            }
-            (VmValue::f32Array2(val1), VmValue::f32Array2(val2)) => {
-                let mut result: [f32; 2] = [0.0; 2];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::sub_f32(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array2(result))
-            }
-            (VmValue::f32Array4(val1), VmValue::f32Array4(val2)) => {
-                let mut result: [f32; 4] = [0.0; 4];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::sub_f32(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array4(result))
-            }
-            (VmValue::f32Array8(val1), VmValue::f32Array8(val2)) => {
-                let mut result: [f32; 8] = [0.0; 8];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::sub_f32(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array8(result))
-            }
-            (VmValue::f64Array2(val1), VmValue::f64Array2(val2)) => {
-                let mut result: [f64; 2] = [0.0; 2];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::sub_f64(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array2(result))
-            }
-            (VmValue::f64Array4(val1), VmValue::f64Array4(val2)) => {
-                let mut result: [f64; 4] = [0.0; 4];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::sub_f64(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array4(result))
-            }
-            (VmValue::f64Array8(val1), VmValue::f64Array8(val2)) => {
-                let mut result: [f64; 8] = [0.0; 8];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::sub_f64(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array8(result))
-            }
-            (_, _) => panic!("Cannot perform substraction between different variants!"),
+            (VmValue::F32(val1), VmValue::F32(val2)) => match VmValue::sub_f32(&val1, &val2) {
+                Some(result) => Ok(VmValue::F32(result)),
+                None => Err(VmError::Infinity),
+            },
+            (VmValue::F64(val1), VmValue::F64(val2)) => match VmValue::sub_f64(&val1, &val2) {
+                Some(result) => Ok(VmValue::F64(result)),
+                None => Err(VmError::Infinity),
+            },
+            (VmValue::Fe25519(val1), VmValue::Fe25519(val2)) => {
+                Ok(VmValue::Fe25519(fe25519::sub(&val1, &val2)))
+            }
+            int_arith_arm!(i32Array2, i32, 2, sub_i32),
+            int_arith_arm!(i32Array4, i32, 4, sub_i32),
+            int_arith_arm!(i32Array8, i32, 8, sub_i32),
+            int_arith_arm!(i32Array16, i32, 16, sub_i32),
+            int_arith_arm!(i32Array32, i32, 32, sub_i32),
+            int_arith_arm!(i32Array64, i32, 64, sub_i32),
+            int_arith_arm!(i32Array128, i32, 128, sub_i32),
+            int_arith_arm!(i32Array256, i32, 256, sub_i32),
+            int_arith_arm!(i64Array2, i64, 2, sub_i64),
+            int_arith_arm!(i64Array4, i64, 4, sub_i64),
+            int_arith_arm!(i64Array8, i64, 8, sub_i64),
+            int_arith_arm!(i64Array16, i64, 16, sub_i64),
+            int_arith_arm!(i64Array32, i64, 32, sub_i64),
+            int_arith_arm!(i64Array64, i64, 64, sub_i64),
+            int_arith_arm!(i64Array128, i64, 128, sub_i64),
+            int_arith_arm!(i64Array256, i64, 256, sub_i64),
+            float_arith_arm!(f32Array2, f32, 2, sub_f32),
+            float_arith_arm!(f32Array4, f32, 4, sub_f32),
+            float_arith_arm!(f32Array8, f32, 8, sub_f32),
+            float_arith_arm!(f32Array16, f32, 16, sub_f32),
+            float_arith_arm!(f32Array32, f32, 32, sub_f32),
+            float_arith_arm!(f32Array64, f32, 64, sub_f32),
+            float_arith_arm!(f32Array128, f32, 128, sub_f32),
+            float_arith_arm!(f32Array256, f32, 256, sub_f32),
+            float_arith_arm!(f64Array2, f64, 2, sub_f64),
+            float_arith_arm!(f64Array4, f64, 4, sub_f64),
+            float_arith_arm!(f64Array8, f64, 8, sub_f64),
+            float_arith_arm!(f64Array16, f64, 16, sub_f64),
+            float_arith_arm!(f64Array32, f64, 32, sub_f64),
+            float_arith_arm!(f64Array64, f64, 64, sub_f64),
+            float_arith_arm!(f64Array128, f64, 128, sub_f64),
+            float_arith_arm!(f64Array256, f64, 256, sub_f64),
+            (_, _) => Err(VmError::TypeMismatch),
         }
     }
 }
@@ -660,6 +2408,14 @@ impl Mul for VmValue {
                 Some(result) => Ok(VmValue::I64(result)),
                 None => Err(VmError::Overflow),
             },
+            (VmValue::I128(val1), VmValue::I128(val2)) => match val1.checked_mul(val2) {
+                Some(result) => Ok(VmValue::I128(result)),
+                None => Err(VmError::Overflow),
+            },
+            (VmValue::U128(val1), VmValue::U128(val2)) => match val1.checked_mul(val2) {
+                Some(result) => Ok(VmValue::U128(result)),
+                None => Err(VmError::Overflow),
+            },
             (VmValue::F32(val1), VmValue::F32(val2)) => match VmValue::mul_f32(&val1, &val2) {
                 Some(result) => Ok(VmValue::F32(result)),
                 None => Err(VmError::Infinity),
@@ -668,163 +2424,42 @@ impl Mul for VmValue {
                 Some(result) => Ok(VmValue::F64(result)),
                 None => Err(VmError::Infinity),
             },
-            (VmValue::i32Array2(val1), VmValue::i32Array2(val2)) => {
-                let mut result: [i32; 2] = [0; 2];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_mul(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array2(result))
-            }
-            (VmValue::i32Array4(val1), VmValue::i32Array4(val2)) => {
-                let mut result: [i32; 4] = [0; 4];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_mul(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array4(result))
-            }
-            (VmValue::i32Array8(val1), VmValue::i32Array8(val2)) => {
-                let mut result: [i32; 8] = [0; 8];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_mul(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array8(result))
-            }
-            (VmValue::i64Array2(val1), VmValue::i64Array2(val2)) => {
-                let mut result: [i64; 2] = [0; 2];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_mul(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array2(result))
-            }
-            (VmValue::i64Array4(val1), VmValue::i64Array4(val2)) => {
-                let mut result: [i64; 4] = [0; 4];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_mul(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array4(result))
-            }
-            (VmValue::i64Array8(val1), VmValue::i64Array8(val2)) => {
-                let mut result: [i64; 8] = [0; 8];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| a.checked_mul(*b).ok_or(VmError::Overflow));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array8(result))
-            }
-            (VmValue::f32Array2(val1), VmValue::f32Array2(val2)) => {
-                let mut result: [f32; 2] = [0.0; 2];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::mul_f32(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array2(result))
-            }
-            (VmValue::f32Array4(val1), VmValue::f32Array4(val2)) => {
-                let mut result: [f32; 4] = [0.0; 4];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::mul_f32(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array4(result))
-            }
-            (VmValue::f32Array8(val1), VmValue::f32Array8(val2)) => {
-                let mut result: [f32; 8] = [0.0; 8];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::mul_f32(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array8(result))
-            }
-            (VmValue::f64Array2(val1), VmValue::f64Array2(val2)) => {
-                let mut result: [f64; 2] = [0.0; 2];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::mul_f64(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array2(result))
-            }
-            (VmValue::f64Array4(val1), VmValue::f64Array4(val2)) => {
-                let mut result: [f64; 4] = [0.0; 4];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::mul_f64(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array4(result))
-            }
-            (VmValue::f64Array8(val1), VmValue::f64Array8(val2)) => {
-                let mut result: [f64; 8] = [0.0; 8];
-                let src = val1
-                    .iter()
-                    .zip(&val2)
-                    .map(|(a, b)| VmValue::mul_f64(a, b).ok_or(VmError::Infinity));
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array8(result))
-            }
-            (_, _) => panic!("Cannot perform multiplication between different variants!"),
+            (VmValue::Fe25519(val1), VmValue::Fe25519(val2)) => {
+                Ok(VmValue::Fe25519(fe25519::mul(&val1, &val2)))
+            }
+            int_mul_arm!(i32Array2, i32, 2, mul_i32),
+            int_mul_arm!(i32Array4, i32, 4, mul_i32),
+            int_mul_arm!(i32Array8, i32, 8, mul_i32),
+            int_mul_arm!(i32Array16, i32, 16, mul_i32),
+            int_mul_arm!(i32Array32, i32, 32, mul_i32),
+            int_mul_arm!(i32Array64, i32, 64, mul_i32),
+            int_mul_arm!(i32Array128, i32, 128, mul_i32),
+            int_mul_arm!(i32Array256, i32, 256, mul_i32),
+            int_mul_arm!(i64Array2, i64, 2, mul_i64),
+            int_mul_arm!(i64Array4, i64, 4, mul_i64),
+            int_mul_arm!(i64Array8, i64, 8, mul_i64),
+            int_mul_arm!(i64Array16, i64, 16, mul_i64),
+            int_mul_arm!(i64Array32, i64, 32, mul_i64),
+            int_mul_arm!(i64Array64, i64, 64, mul_i64),
+            int_mul_arm!(i64Array128, i64, 128, mul_i64),
+            int_mul_arm!(i64Array256, i64, 256, mul_i64),
+            float_arith_arm!(f32Array2, f32, 2, mul_f32),
+            float_arith_arm!(f32Array4, f32, 4, mul_f32),
+            float_arith_arm!(f32Array8, f32, 8, mul_f32),
+            float_arith_arm!(f32Array16, f32, 16, mul_f32),
+            float_arith_arm!(f32Array32, f32, 32, mul_f32),
+            float_arith_arm!(f32Array64, f32, 64, mul_f32),
+            float_arith_arm!(f32Array128, f32, 128, mul_f32),
+            float_arith_arm!(f32Array256, f32, 256, mul_f32),
+            float_arith_arm!(f64Array2, f64, 2, mul_f64),
+            float_arith_arm!(f64Array4, f64, 4, mul_f64),
+            float_arith_arm!(f64Array8, f64, 8, mul_f64),
+            float_arith_arm!(f64Array16, f64, 16, mul_f64),
+            float_arith_arm!(f64Array32, f64, 32, mul_f64),
+            float_arith_arm!(f64Array64, f64, 64, mul_f64),
+            float_arith_arm!(f64Array128, f64, 128, mul_f64),
+            float_arith_arm!(f64Array256, f64, 256, mul_f64),
+            (_, _) => Err(VmError::TypeMismatch),
         }
     }
 }
@@ -854,6 +2489,14 @@ impl Div for VmValue {
                     None => Err(VmError::Overflow),
                 }
             }
+            (VmValue::I128(val1), VmValue::I128(val2)) => {
+                let (quotient, _) = VmValue::sdivmod128(val1, val2)?;
+                Ok(VmValue::I128(quotient))
+            }
+            (VmValue::U128(val1), VmValue::U128(val2)) => {
+                let (quotient, _) = VmValue::udivmod128(val1, val2)?;
+                Ok(VmValue::U128(quotient))
+            }
             (VmValue::F32(val1), VmValue::F32(val2)) => {
                 if val2 == 0.0 {
                     return Err(VmError::DivideByZero);
@@ -874,199 +2517,42 @@ impl Div for VmValue {
                     None => Err(VmError::Overflow),
                 }
             }
-            (VmValue::i32Array2(val1), VmValue::i32Array2(val2)) => {
-                let mut result: [i32; 2] = [0; 2];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    a.checked_div(*b).ok_or(VmError::Overflow)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array2(result))
-            }
-            (VmValue::i32Array4(val1), VmValue::i32Array4(val2)) => {
-                let mut result: [i32; 4] = [0; 4];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    a.checked_div(*b).ok_or(VmError::Overflow)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array4(result))
-            }
-            (VmValue::i32Array8(val1), VmValue::i32Array8(val2)) => {
-                let mut result: [i32; 8] = [0; 8];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    a.checked_div(*b).ok_or(VmError::Overflow)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array8(result))
-            }
-            (VmValue::i64Array2(val1), VmValue::i64Array2(val2)) => {
-                let mut result: [i64; 2] = [0; 2];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    a.checked_div(*b).ok_or(VmError::Overflow)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array2(result))
-            }
-            (VmValue::i64Array4(val1), VmValue::i64Array4(val2)) => {
-                let mut result: [i64; 4] = [0; 4];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    a.checked_div(*b).ok_or(VmError::Overflow)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array4(result))
-            }
-            (VmValue::i64Array8(val1), VmValue::i64Array8(val2)) => {
-                let mut result: [i64; 8] = [0; 8];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    a.checked_div(*b).ok_or(VmError::Overflow)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array8(result))
-            }
-            (VmValue::f32Array2(val1), VmValue::f32Array2(val2)) => {
-                let mut result: [f32; 2] = [0.0; 2];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0.0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    VmValue::div_f32(a, b).ok_or(VmError::Infinity)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array2(result))
-            }
-            (VmValue::f32Array4(val1), VmValue::f32Array4(val2)) => {
-                let mut result: [f32; 4] = [0.0; 4];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0.0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    VmValue::div_f32(a, b).ok_or(VmError::Infinity)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array4(result))
-            }
-            (VmValue::f32Array8(val1), VmValue::f32Array8(val2)) => {
-                let mut result: [f32; 8] = [0.0; 8];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0.0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    VmValue::div_f32(a, b).ok_or(VmError::Infinity)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array8(result))
-            }
-            (VmValue::f64Array2(val1), VmValue::f64Array2(val2)) => {
-                let mut result: [f64; 2] = [0.0; 2];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0.0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    VmValue::div_f64(a, b).ok_or(VmError::Infinity)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array2(result))
-            }
-            (VmValue::f64Array4(val1), VmValue::f64Array4(val2)) => {
-                let mut result: [f64; 4] = [0.0; 4];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0.0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    VmValue::div_f64(a, b).ok_or(VmError::Infinity)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array4(result))
-            }
-            (VmValue::f64Array8(val1), VmValue::f64Array8(val2)) => {
-                let mut result: [f64; 8] = [0.0; 8];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0.0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    VmValue::div_f64(a, b).ok_or(VmError::Infinity)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array8(result))
-            }
-            (_, _) => panic!("Cannot perform division between different variants!"),
+            (VmValue::Fe25519(val1), VmValue::Fe25519(val2)) => {
+                Ok(VmValue::Fe25519(fe25519::div(&val1, &val2)?))
+            }
+            int_divrem_arm!(i32Array2, i32, 2, div_i32),
+            int_divrem_arm!(i32Array4, i32, 4, div_i32),
+            int_divrem_arm!(i32Array8, i32, 8, div_i32),
+            int_divrem_arm!(i32Array16, i32, 16, div_i32),
+            int_divrem_arm!(i32Array32, i32, 32, div_i32),
+            int_divrem_arm!(i32Array64, i32, 64, div_i32),
+            int_divrem_arm!(i32Array128, i32, 128, div_i32),
+            int_divrem_arm!(i32Array256, i32, 256, div_i32),
+            int_divrem_arm!(i64Array2, i64, 2, div_i64),
+            int_divrem_arm!(i64Array4, i64, 4, div_i64),
+            int_divrem_arm!(i64Array8, i64, 8, div_i64),
+            int_divrem_arm!(i64Array16, i64, 16, div_i64),
+            int_divrem_arm!(i64Array32, i64, 32, div_i64),
+            int_divrem_arm!(i64Array64, i64, 64, div_i64),
+            int_divrem_arm!(i64Array128, i64, 128, div_i64),
+            int_divrem_arm!(i64Array256, i64, 256, div_i64),
+            float_divrem_arm!(f32Array2, f32, 2, div_f32),
+            float_divrem_arm!(f32Array4, f32, 4, div_f32),
+            float_divrem_arm!(f32Array8, f32, 8, div_f32),
+            float_divrem_arm!(f32Array16, f32, 16, div_f32),
+            float_divrem_arm!(f32Array32, f32, 32, div_f32),
+            float_divrem_arm!(f32Array64, f32, 64, div_f32),
+            float_divrem_arm!(f32Array128, f32, 128, div_f32),
+            float_divrem_arm!(f32Array256, f32, 256, div_f32),
+            float_divrem_arm!(f64Array2, f64, 2, div_f64),
+            float_divrem_arm!(f64Array4, f64, 4, div_f64),
+            float_divrem_arm!(f64Array8, f64, 8, div_f64),
+            float_divrem_arm!(f64Array16, f64, 16, div_f64),
+            float_divrem_arm!(f64Array32, f64, 32, div_f64),
+            float_divrem_arm!(f64Array64, f64, 64, div_f64),
+            float_divrem_arm!(f64Array128, f64, 128, div_f64),
+            float_divrem_arm!(f64Array256, f64, 256, div_f64),
+            (_, _) => Err(VmError::TypeMismatch),
         }
     }
 }
@@ -1096,6 +2582,14 @@ impl Rem for VmValue {
                     None => Err(VmError::Overflow),
                 }
             }
+            (VmValue::I128(val1), VmValue::I128(val2)) => {
+                let (_, remainder) = VmValue::sdivmod128(val1, val2)?;
+                Ok(VmValue::I128(remainder))
+            }
+            (VmValue::U128(val1), VmValue::U128(val2)) => {
+                let (_, remainder) = VmValue::udivmod128(val1, val2)?;
+                Ok(VmValue::U128(remainder))
+            }
             (VmValue::F32(val1), VmValue::F32(val2)) => {
                 if val2 == 0.0 {
                     return Err(VmError::DivideByZero);
@@ -1116,210 +2610,78 @@ impl Rem for VmValue {
                     None => Err(VmError::Overflow),
                 }
             }
-            (VmValue::i32Array2(val1), VmValue::i32Array2(val2)) => {
-                let mut result: [i32; 2] = [0; 2];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    a.checked_rem(*b).ok_or(VmError::Overflow)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array2(result))
-            }
-            (VmValue::i32Array4(val1), VmValue::i32Array4(val2)) => {
-                let mut result: [i32; 4] = [0; 4];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    a.checked_rem(*b).ok_or(VmError::Overflow)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array4(result))
-            }
-            (VmValue::i32Array8(val1), VmValue::i32Array8(val2)) => {
-                let mut result: [i32; 8] = [0; 8];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    a.checked_rem(*b).ok_or(VmError::Overflow)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i32Array8(result))
-            }
-            (VmValue::i64Array2(val1), VmValue::i64Array2(val2)) => {
-                let mut result: [i64; 2] = [0; 2];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    a.checked_rem(*b).ok_or(VmError::Overflow)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array2(result))
-            }
-            (VmValue::i64Array4(val1), VmValue::i64Array4(val2)) => {
-                let mut result: [i64; 4] = [0; 4];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    a.checked_rem(*b).ok_or(VmError::Overflow)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array4(result))
-            }
-            (VmValue::i64Array8(val1), VmValue::i64Array8(val2)) => {
-                let mut result: [i64; 8] = [0; 8];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    a.checked_rem(*b).ok_or(VmError::Overflow)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::i64Array8(result))
-            }
-            (VmValue::f32Array2(val1), VmValue::f32Array2(val2)) => {
-                let mut result: [f32; 2] = [0.0; 2];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0.0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    VmValue::rem_f32(a, b).ok_or(VmError::Infinity)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array2(result))
-            }
-            (VmValue::f32Array4(val1), VmValue::f32Array4(val2)) => {
-                let mut result: [f32; 4] = [0.0; 4];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0.0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    VmValue::rem_f32(a, b).ok_or(VmError::Infinity)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array4(result))
-            }
-            (VmValue::f32Array8(val1), VmValue::f32Array8(val2)) => {
-                let mut result: [f32; 8] = [0.0; 8];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0.0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    VmValue::rem_f32(a, b).ok_or(VmError::Infinity)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f32Array8(result))
-            }
-            (VmValue::f64Array2(val1), VmValue::f64Array2(val2)) => {
-                let mut result: [f64; 2] = [0.0; 2];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0.0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    VmValue::rem_f64(a, b).ok_or(VmError::Infinity)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array2(result))
-            }
-            (VmValue::f64Array4(val1), VmValue::f64Array4(val2)) => {
-                let mut result: [f64; 4] = [0.0; 4];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0.0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    VmValue::rem_f64(a, b).ok_or(VmError::Infinity)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
-                }
-
-                Ok(VmValue::f64Array4(result))
-            }
-            (VmValue::f64Array8(val1), VmValue::f64Array8(val2)) => {
-                let mut result: [f64; 8] = [0.0; 8];
-                let src = val1.iter().zip(&val2).map(|(a, b)| {
-                    if *b == 0.0 {
-                        return Err(VmError::DivideByZero);
-                    }
-
-                    VmValue::rem_f64(a, b).ok_or(VmError::Infinity)
-                });
-
-                for (r, v) in result.iter_mut().zip(src) {
-                    *r = v.unwrap();
+            // Field division is exact (every nonzero element has an
+            // inverse), so there is no partial quotient left over: the
+            // remainder is zero whenever the divisor is nonzero, leaving
+            // `DivideByZero` as the only error `Rem` can produce here too.
+            (VmValue::Fe25519(_), VmValue::Fe25519(val2)) => {
+                if fe25519::is_zero(&val2) {
+                    return Err(VmError::DivideByZero);
                 }
 
-                Ok(VmValue::f64Array8(result))
-            }
-            (_, _) => panic!("Cannot perform division between different variants!"),
+                Ok(VmValue::Fe25519(fe25519::zero()))
+            }
+            int_divrem_arm!(i32Array2, i32, 2, rem_i32),
+            int_divrem_arm!(i32Array4, i32, 4, rem_i32),
+            int_divrem_arm!(i32Array8, i32, 8, rem_i32),
+            int_divrem_arm!(i32Array16, i32, 16, rem_i32),
+            int_divrem_arm!(i32Array32, i32, 32, rem_i32),
+            int_divrem_arm!(i32Array64, i32, 64, rem_i32),
+            int_divrem_arm!(i32Array128, i32, 128, rem_i32),
+            int_divrem_arm!(i32Array256, i32, 256, rem_i32),
+            int_divrem_arm!(i64Array2, i64, 2, rem_i64),
+            int_divrem_arm!(i64Array4, i64, 4, rem_i64),
+            int_divrem_arm!(i64Array8, i64, 8, rem_i64),
+            int_divrem_arm!(i64Array16, i64, 16, rem_i64),
+            int_divrem_arm!(i64Array32, i64, 32, rem_i64),
+            int_divrem_arm!(i64Array64, i64, 64, rem_i64),
+            int_divrem_arm!(i64Array128, i64, 128, rem_i64),
+            int_divrem_arm!(i64Array256, i64, 256, rem_i64),
+            float_divrem_arm!(f32Array2, f32, 2, rem_f32),
+            float_divrem_arm!(f32Array4, f32, 4, rem_f32),
+            float_divrem_arm!(f32Array8, f32, 8, rem_f32),
+            float_divrem_arm!(f32Array16, f32, 16, rem_f32),
+            float_divrem_arm!(f32Array32, f32, 32, rem_f32),
+            float_divrem_arm!(f32Array64, f32, 64, rem_f32),
+            float_divrem_arm!(f32Array128, f32, 128, rem_f32),
+            float_divrem_arm!(f32Array256, f32, 256, rem_f32),
+            float_divrem_arm!(f64Array2, f64, 2, rem_f64),
+            float_divrem_arm!(f64Array4, f64, 4, rem_f64),
+            float_divrem_arm!(f64Array8, f64, 8, rem_f64),
+            float_divrem_arm!(f64Array16, f64, 16, rem_f64),
+            float_divrem_arm!(f64Array32, f64, 32, rem_f64),
+            float_divrem_arm!(f64Array64, f64, 64, rem_f64),
+            float_divrem_arm!(f64Array128, f64, 128, rem_f64),
+            float_divrem_arm!(f64Array256, f64, 256, rem_f64),
+            (_, _) => Err(VmError::TypeMismatch),
         }
     }
 }
 
 impl fmt::Debug for VmValue {
+    /// Built on top of `describe()` (`"{kind}: {lanes}"`, e.g. `"F32x4:
+    /// [1.0, 2.0, 3.0, 4.0]"`) so the shape shown here and the shape
+    /// `describe()` reports for the same value can never drift apart.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.describe())?;
+        self.fmt_lanes(f)
+    }
+}
+
+impl VmValue {
+    /// Renders just this value's lanes (no type/shape prefix — see
+    /// `impl Debug`, which prepends `describe()`'s output).
+    fn fmt_lanes(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             VmValue::I32(val) => write!(f, "{}", val),
             VmValue::I64(val) => write!(f, "{}", val),
+            VmValue::I128(val) => write!(f, "{}", val),
+            VmValue::U128(val) => write!(f, "{}", val),
             VmValue::F32(val) => write!(f, "{}", val),
             VmValue::F64(val) => write!(f, "{}", val),
+            VmValue::F16(val) => write!(f, "{}", val),
+            VmValue::Bf16(val) => write!(f, "{}", val),
+            VmValue::Fe25519(val) => write!(f, "{:?}", fe25519::freeze(&val)),
             VmValue::i32Array2(val) => write!(f, "{:?}", val.to_vec()),
             VmValue::i32Array4(val) => write!(f, "{:?}", val.to_vec()),
             VmValue::i32Array8(val) => write!(f, "{:?}", val.to_vec()),
@@ -1352,6 +2714,198 @@ impl fmt::Debug for VmValue {
             VmValue::f64Array64(val) => write!(f, "{:?}", val.to_vec()),
             VmValue::f64Array128(val) => write!(f, "{:?}", val.to_vec()),
             VmValue::f64Array256(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::f16Array2(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::f16Array4(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::f16Array8(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::f16Array16(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::f16Array32(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::f16Array64(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::f16Array128(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::f16Array256(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::bf16Array2(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::bf16Array4(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::bf16Array8(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::bf16Array16(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::bf16Array32(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::bf16Array64(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::bf16Array128(val) => write!(f, "{:?}", val.to_vec()),
+            VmValue::bf16Array256(val) => write!(f, "{:?}", val.to_vec()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(val: VmValue) -> VmValue {
+        let bytes = val.to_canonical_bytes();
+        VmValue::from_canonical_bytes(bytes[0], &bytes[1..]).unwrap()
+    }
+
+    #[test]
+    fn scalar_ints_roundtrip() {
+        assert_eq!(roundtrip(VmValue::I32(-42)), VmValue::I32(-42));
+        assert_eq!(roundtrip(VmValue::I64(i64::MIN)), VmValue::I64(i64::MIN));
+        assert_eq!(roundtrip(VmValue::I128(i128::MAX)), VmValue::I128(i128::MAX));
+        assert_eq!(roundtrip(VmValue::U128(u128::MAX)), VmValue::U128(u128::MAX));
+    }
+
+    #[test]
+    fn scalar_floats_roundtrip() {
+        assert_eq!(roundtrip(VmValue::F32(1.5)), VmValue::F32(1.5));
+        assert_eq!(roundtrip(VmValue::F64(-2.25)), VmValue::F64(-2.25));
+        assert_eq!(roundtrip(VmValue::F16(f16::from_f32(1.5))), VmValue::F16(f16::from_f32(1.5)));
+        assert_eq!(roundtrip(VmValue::Bf16(bf16::from_f32(1.5))), VmValue::Bf16(bf16::from_f32(1.5)));
+    }
+
+    #[test]
+    fn negative_zero_canonicalizes_to_positive_zero() {
+        assert_eq!(VmValue::F32(-0.0).to_canonical_bytes(), VmValue::F32(0.0).to_canonical_bytes());
+        assert_eq!(VmValue::F64(-0.0).to_canonical_bytes(), VmValue::F64(0.0).to_canonical_bytes());
+    }
+
+    #[test]
+    fn every_nan_bit_pattern_canonicalizes_to_the_same_bytes() {
+        let quiet = f32::from_bits(0x7fc0_1234);
+        let signaling = f32::from_bits(0xffa0_0001);
+        assert_eq!(VmValue::F32(quiet).to_canonical_bytes(), VmValue::F32(signaling).to_canonical_bytes());
+        assert_eq!(VmValue::F32(f32::NAN).to_canonical_bytes(), VmValue::F32(quiet).to_canonical_bytes());
+    }
+
+    #[test]
+    fn fe25519_roundtrips() {
+        let val = VmValue::Fe25519([1, 2, 3, 4, 5]);
+        assert_eq!(roundtrip(val), val);
+    }
+
+    #[test]
+    fn fe25519_canonical_bytes_reduce_modulo_p() {
+        // Two loosely-reduced limb representations of the same residue
+        // (differing by exactly the field modulus `p = 2^255 - 19`) must
+        // serialize to identical canonical bytes, not just compare `eq` -
+        // otherwise two honest nodes computing "the same" field element
+        // could hash it differently.
+        let mask = (1u64 << 51) - 1;
+        let p: [u64; 5] = [mask - 18, mask, mask, mask, mask];
+        let canonical = [1u64, 2, 3, 4, 5];
+        let unreduced = [
+            canonical[0] + p[0],
+            canonical[1] + p[1],
+            canonical[2] + p[2],
+            canonical[3] + p[3],
+            canonical[4] + p[4],
+        ];
+
+        assert_eq!(
+            VmValue::Fe25519(canonical).to_canonical_bytes(),
+            VmValue::Fe25519(unreduced).to_canonical_bytes()
+        );
+    }
+
+    #[test]
+    fn array_variants_roundtrip_one_width_per_family() {
+        assert_eq!(roundtrip(VmValue::i32Array4([1, -2, 3, -4])), VmValue::i32Array4([1, -2, 3, -4]));
+        assert_eq!(roundtrip(VmValue::i64Array4([1, -2, 3, -4])), VmValue::i64Array4([1, -2, 3, -4]));
+        assert_eq!(roundtrip(VmValue::f32Array4([1.0, -0.0, f32::NAN, 4.0])).to_canonical_bytes(), roundtrip(VmValue::f32Array4([1.0, 0.0, f32::NAN, 4.0])).to_canonical_bytes());
+        assert_eq!(roundtrip(VmValue::f64Array4([1.0, 2.0, 3.0, 4.0])), VmValue::f64Array4([1.0, 2.0, 3.0, 4.0]));
+
+        let f16_vals = [f16::from_f32(1.0), f16::from_f32(-2.0), f16::from_f32(3.5), f16::ZERO];
+        assert_eq!(roundtrip(VmValue::f16Array4(f16_vals)), VmValue::f16Array4(f16_vals));
+
+        let bf16_vals = [bf16::from_f32(1.0), bf16::from_f32(-2.0), bf16::from_f32(3.5), bf16::ZERO];
+        assert_eq!(roundtrip(VmValue::bf16Array4(bf16_vals)), VmValue::bf16Array4(bf16_vals));
+    }
+
+    #[test]
+    fn unknown_tag_is_a_type_mismatch() {
+        assert!(VmValue::from_canonical_bytes(255, &[]).is_err());
+    }
+
+    #[test]
+    fn rotate_left_shifts_lanes_down() {
+        let val = VmValue::i32Array8([1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(val.rotate_left(2).unwrap(), VmValue::i32Array8([3, 4, 5, 6, 7, 8, 1, 2]));
+    }
+
+    #[test]
+    fn rotate_right_shifts_lanes_up() {
+        let val = VmValue::i32Array8([1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(val.rotate_right(2).unwrap(), VmValue::i32Array8([7, 8, 1, 2, 3, 4, 5, 6]));
+    }
+
+    #[test]
+    fn rotate_by_zero_and_by_full_length_are_no_ops() {
+        let val = VmValue::f64Array4([1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(val.rotate_left(0).unwrap(), val);
+        assert_eq!(val.rotate_left(4).unwrap(), val);
+    }
+
+    #[test]
+    fn rotate_wraps_negative_and_oversized_shifts() {
+        let val = VmValue::i64Array4([1, 2, 3, 4]);
+        assert_eq!(val.rotate_left(-1).unwrap(), val.rotate_right(1).unwrap());
+        assert_eq!(val.rotate_left(9).unwrap(), val.rotate_left(1).unwrap());
+    }
+
+    #[test]
+    fn rotate_is_a_type_mismatch_for_non_array_variants() {
+        assert!(VmValue::I32(1).rotate_left(1).is_err());
+        assert!(VmValue::F16(f16::from_f32(1.0)).rotate_left(1).is_err());
+    }
+
+    #[test]
+    fn describe_reports_scalar_shape() {
+        let desc = VmValue::F32(1.0).describe();
+        assert_eq!(desc.kind, VmElementKind::F32);
+        assert_eq!(desc.lane_count, 1);
+        assert_eq!(desc.byte_size, 4);
+        assert_eq!(format!("{}", desc), "F32");
+    }
+
+    #[test]
+    fn describe_reports_array_shape() {
+        let desc = VmValue::i64Array4([0; 4]).describe();
+        assert_eq!(desc.kind, VmElementKind::I64);
+        assert_eq!(desc.lane_count, 4);
+        assert_eq!(desc.byte_size, 32);
+        assert_eq!(format!("{}", desc), "I64x4");
+    }
+
+    #[test]
+    fn debug_output_is_prefixed_with_describe() {
+        let val = VmValue::i32Array2([1, 2]);
+        assert_eq!(format!("{:?}", val), format!("{}: [1, 2]", val.describe()));
+    }
+
+    #[test]
+    fn plain_render_matches_to_plain_with_no_color() {
+        let val = VmValue::i32Array2([1, 2]);
+        let rendered = val.render(RenderStyle::plain());
+        assert_eq!(rendered.to_plain(), "[1, 2]");
+        assert_eq!(rendered.spans[0].color, Color::Default);
+    }
+
+    #[test]
+    fn ansi_render_colors_scalars_int_arrays_and_float_arrays_differently() {
+        let scalar = VmValue::I32(1).render(RenderStyle::ansi());
+        let int_array = VmValue::i32Array2([1, 2]).render(RenderStyle::ansi());
+        let float_array = VmValue::f64Array2([1.0, 2.0]).render(RenderStyle::ansi());
+
+        assert_eq!(scalar.spans[0].color, Color::Scalar);
+        assert_eq!(int_array.spans[0].color, Color::IntArray);
+        assert_eq!(float_array.spans[0].color, Color::FloatArray);
+        assert_ne!(int_array.spans[0].color, float_array.spans[0].color);
+
+        let ansi = int_array.to_ansi();
+        assert!(ansi.contains("\x1b["));
+        assert!(ansi.ends_with("\x1b[0m"));
+    }
+
+    #[test]
+    fn render_elides_arrays_past_max_elements() {
+        let val = VmValue::i32Array256([1; 256]);
+        let rendered = val.render(RenderStyle::plain().with_max_elements(3));
+        assert_eq!(rendered.to_plain(), "[1, 1, 1, … +253 more]");
+    }
+}
@@ -0,0 +1,144 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Static type-checking pass over a raw instruction stream, modeled on how a
+//! WASM validator walks an operand stack of *types* instead of running the
+//! program. `validate_structure` only ever checks one already-isolated
+//! value's encoding; `verify_stream` checks that a whole stream of push and
+//! arithmetic opcodes is well-typed end to end, before any of it executes.
+//!
+//! The abstract stack holds a `VmType` per value a real run would have on
+//! its operand stack. A push opcode (anything `VmType::from_op` resolves)
+//! consumes its literal payload - exactly `byte_size()` bytes, right after
+//! the opcode byte - and pushes its `VmType`. A binary arithmetic opcode
+//! (`Add`/`Sub`/`Mul`/`Div`/`Rem`, mirroring `VmValue`'s `Add`/`Sub`/`Mul`/
+//! `Div`/`Rem` trait impls) pops two operand types and pushes one result
+//! type. Two operand types are compatible either because they're identical,
+//! or because one is an array and the other is a bare scalar matching that
+//! array's `array_accepts()` lane type - the latter is a scalar-broadcast
+//! op, and it's the reason `is_i32`/`is_i64`/`is_f32`/`is_f64` and
+//! `array_accepts` are what drive the compatibility check instead of a
+//! plain `==`. A well-typed stream ends with exactly one type left on the
+//! stack: the stream's single result.
+
+use super::r#type::VmType;
+use crate::instruction_set::Instruction;
+
+/// Why `VmType::verify_stream` rejected a stream, carrying the byte offset
+/// of the first offending opcode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifyError {
+    /// `op` at this offset doesn't resolve to any known instruction.
+    UnknownOpcode(usize),
+
+    /// A push instruction's literal payload runs past the end of the
+    /// stream.
+    TruncatedOperand(usize),
+
+    /// An arithmetic instruction needed an operand the abstract stack
+    /// didn't have.
+    StackUnderflow(usize),
+
+    /// An arithmetic instruction's two operand types aren't compatible.
+    TypeMismatch(usize),
+
+    /// The stream ended with zero or more than one value left on the
+    /// stack.
+    UnbalancedStack,
+}
+
+impl VmType {
+    /// Type-checks `ops` as a whole instruction stream, returning `Ok(())`
+    /// iff every instruction's operands are available and well-typed and
+    /// exactly one value remains at the end.
+    pub fn verify_stream(ops: &[u8]) -> Result<(), VerifyError> {
+        let mut stack: Vec<VmType> = Vec::new();
+        let mut i = 0;
+
+        while i < ops.len() {
+            let offset = i;
+            let op = ops[i];
+
+            if let Some(ty) = VmType::from_op(op) {
+                let end = i + 1 + ty.byte_size();
+                if end > ops.len() {
+                    return Err(VerifyError::TruncatedOperand(offset));
+                }
+                stack.push(ty);
+                i = end;
+                continue;
+            }
+
+            match Instruction::from_repr(op) {
+                Some(Instruction::Add)
+                | Some(Instruction::Sub)
+                | Some(Instruction::Mul)
+                | Some(Instruction::Div)
+                | Some(Instruction::Rem) => {
+                    let rhs = stack.pop().ok_or(VerifyError::StackUnderflow(offset))?;
+                    let lhs = stack.pop().ok_or(VerifyError::StackUnderflow(offset))?;
+                    let result = compatible(lhs, rhs).ok_or(VerifyError::TypeMismatch(offset))?;
+                    stack.push(result);
+                    i += 1;
+                }
+                _ => return Err(VerifyError::UnknownOpcode(offset)),
+            }
+        }
+
+        if stack.len() == 1 {
+            Ok(())
+        } else {
+            Err(VerifyError::UnbalancedStack)
+        }
+    }
+}
+
+/// Whether `lhs`/`rhs` can feed the same arithmetic op, and if so, the
+/// result type it produces. Two identical types are always compatible. An
+/// array and a bare scalar are also compatible - a scalar broadcast - when
+/// the other operand is actually a scalar (not another array) and its
+/// numeric family matches the array's lane type; the result is the array
+/// type, since broadcasting never shrinks the stack value back to a scalar.
+/// Two arrays of the same element but different lane counts are never
+/// compatible.
+fn compatible(lhs: VmType, rhs: VmType) -> Option<VmType> {
+    if lhs == rhs {
+        return Some(lhs);
+    }
+
+    if let Some(lane) = lhs.array_accepts() {
+        if !rhs.is_array() && same_family(lane, rhs) {
+            return Some(lhs);
+        }
+    }
+
+    if let Some(lane) = rhs.array_accepts() {
+        if !lhs.is_array() && same_family(lane, lhs) {
+            return Some(rhs);
+        }
+    }
+
+    None
+}
+
+fn same_family(a: VmType, b: VmType) -> bool {
+    (a.is_i32() && b.is_i32())
+        || (a.is_i64() && b.is_i64())
+        || (a.is_f32() && b.is_f32())
+        || (a.is_f64() && b.is_f64())
+}
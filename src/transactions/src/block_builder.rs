@@ -0,0 +1,238 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Packs a mempool's ready transactions into a block, maximizing total fee
+//! within a byte budget.
+//!
+//! A ready set is really a bundle of independent sender chains threaded
+//! together by `creator_signing_address()` -> `next_address()` links, and
+//! a chain can only ever be included as an unbroken prefix - skipping a
+//! link invalidates everything after it. `BlockBuilder` treats each whole
+//! chain-prefix as the unit of selection: for every chain it finds the
+//! prefix length with the best cumulative fee-per-byte, then repeatedly
+//! commits whichever candidate chain's best prefix currently has the
+//! highest rate among those that still fit the remaining budget. This is
+//! the standard greedy approximation to knapsack, just operating over
+//! chain-prefixes instead of individual items.
+
+use crate::mempool::{cmp_fee_rate, fee_as_u64};
+use crate::Tx;
+use account::NormalAddress;
+use hashbrown::{HashMap, HashSet};
+use patricia_trie::{TrieDB, TrieDBMut};
+use persistence::{Codec, DbHasher};
+use std::cmp::Ordering;
+
+/// The result of a single `BlockBuilder::build` call.
+pub struct PackedBlock {
+    /// Selected transactions, in chain + insertion order.
+    pub txs: Vec<Tx>,
+
+    /// Sum of `fee_as_u64(&tx.fee())` over `txs`.
+    pub total_fee: u64,
+
+    /// Sum of `tx.byte_size()` over `txs`.
+    pub total_bytes: usize,
+}
+
+pub struct BlockBuilder {
+    max_bytes: usize,
+}
+
+impl BlockBuilder {
+    pub fn new(max_bytes: usize) -> BlockBuilder {
+        BlockBuilder { max_bytes }
+    }
+
+    /// Greedily packs `ready` (a mempool's ready set, order doesn't
+    /// matter) into a block, validating and applying through `trie_mut`.
+    /// Each candidate's `validate` call is run against a fresh read-only
+    /// view re-derived from `trie_mut`'s current root, so it sees every
+    /// earlier commit made during this build - including earlier links of
+    /// its own chain, whose `creator_signing_address` only lands on-trie
+    /// once the prior link has been applied.
+    pub fn build(
+        &self,
+        ready: Vec<Tx>,
+        trie_mut: &mut TrieDBMut<DbHasher, Codec>,
+    ) -> PackedBlock {
+        let mut chains: Vec<ChainCandidate> = Self::group_chains(ready)
+            .into_iter()
+            .map(ChainCandidate::new)
+            .collect();
+
+        let mut committed = Vec::new();
+        let mut total_fee = 0u64;
+        let mut total_bytes = 0usize;
+
+        loop {
+            let remaining = self.max_bytes.saturating_sub(total_bytes);
+            let mut best: Option<(usize, usize)> = None; // (chain index, prefix length)
+
+            for (ci, chain) in chains.iter().enumerate() {
+                let pl = match chain.best_prefix_within(remaining) {
+                    Some(pl) => pl,
+                    None => continue,
+                };
+                let is_better = match best {
+                    None => true,
+                    Some((bci, bpl)) => {
+                        let b = &chains[bci];
+                        cmp_fee_rate(
+                            chain.prefix_fee[pl - 1],
+                            chain.prefix_bytes[pl - 1],
+                            b.prefix_fee[bpl - 1],
+                            b.prefix_bytes[bpl - 1],
+                        ) == Ordering::Greater
+                    }
+                };
+                if is_better {
+                    best = Some((ci, pl));
+                }
+            }
+
+            let (ci, prefix_len) = match best {
+                Some(b) => b,
+                None => break,
+            };
+            let chain = chains.remove(ci);
+
+            // Validate and apply the chosen prefix one link at a time;
+            // a chain can never recover from a failed link, since every
+            // later link depends on the state transition the one before
+            // it produced, so the whole remaining tail is dropped with it.
+            // Each link is validated against a trie view re-opened at
+            // `trie_mut`'s current root, since a link beyond the first
+            // only validates once the prior link's commit is visible.
+            let mut accepted = 0;
+            for tx in chain.txs.iter().take(prefix_len) {
+                let current_root = *trie_mut.root();
+                let valid = {
+                    let view = TrieDB::new(trie_mut.db(), &current_root)
+                        .expect("trie_mut's current root is always valid");
+                    tx.validate(&view)
+                };
+                if !valid {
+                    break;
+                }
+                tx.apply(trie_mut);
+                accepted += 1;
+            }
+
+            if accepted > 0 {
+                total_fee += chain.prefix_fee[accepted - 1];
+                total_bytes += chain.prefix_bytes[accepted - 1];
+                committed.extend(chain.txs.into_iter().take(accepted));
+            }
+        }
+
+        PackedBlock {
+            txs: committed,
+            total_fee,
+            total_bytes,
+        }
+    }
+
+    /// Splits `txs` into maximal sender chains, linked by
+    /// `creator_signing_address()` following the previous link's
+    /// `next_address()`. Each one-time-use address can only be the start
+    /// of one link, so every chain here is a simple, unambiguous path.
+    fn group_chains(txs: Vec<Tx>) -> Vec<Vec<Tx>> {
+        let mut by_address: HashMap<NormalAddress, Tx> = txs
+            .into_iter()
+            .map(|tx| (tx.creator_signing_address(), tx))
+            .collect();
+
+        let mut starts: HashSet<NormalAddress> = by_address.keys().cloned().collect();
+        for tx in by_address.values() {
+            starts.remove(&tx.next_address());
+        }
+
+        let mut chains = Vec::new();
+        for start in starts {
+            let mut chain = Vec::new();
+            let mut cursor = start;
+            while let Some(tx) = by_address.remove(&cursor) {
+                cursor = tx.next_address();
+                chain.push(tx);
+            }
+            chains.push(chain);
+        }
+        chains
+    }
+}
+
+/// A sender chain with precomputed cumulative fee/byte prefix sums, so
+/// evaluating candidate prefix lengths doesn't re-walk the chain.
+struct ChainCandidate {
+    txs: Vec<Tx>,
+    prefix_fee: Vec<u64>,
+    prefix_bytes: Vec<usize>,
+}
+
+impl ChainCandidate {
+    fn new(txs: Vec<Tx>) -> ChainCandidate {
+        let mut prefix_fee = Vec::with_capacity(txs.len());
+        let mut prefix_bytes = Vec::with_capacity(txs.len());
+        let mut fee_acc = 0u64;
+        let mut byte_acc = 0usize;
+
+        for tx in &txs {
+            fee_acc += fee_as_u64(&tx.fee());
+            byte_acc += tx.byte_size();
+            prefix_fee.push(fee_acc);
+            prefix_bytes.push(byte_acc);
+        }
+
+        ChainCandidate {
+            txs,
+            prefix_fee,
+            prefix_bytes,
+        }
+    }
+
+    /// The prefix length (1-based) with the best cumulative fee-per-byte
+    /// among those that fit within `budget` bytes, or `None` if even the
+    /// single-transaction prefix doesn't fit.
+    fn best_prefix_within(&self, budget: usize) -> Option<usize> {
+        let mut best_len: Option<usize> = None;
+
+        for i in 0..self.txs.len() {
+            if self.prefix_bytes[i] > budget {
+                break;
+            }
+            let is_better = match best_len {
+                None => true,
+                Some(len) => {
+                    let bi = len - 1;
+                    cmp_fee_rate(
+                        self.prefix_fee[i],
+                        self.prefix_bytes[i],
+                        self.prefix_fee[bi],
+                        self.prefix_bytes[bi],
+                    ) == Ordering::Greater
+                }
+            };
+            if is_better {
+                best_len = Some(i + 1);
+            }
+        }
+
+        best_len
+    }
+}
@@ -0,0 +1,136 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Rollback journaling for trie mutations, so a block that fails partway
+//! through `apply`-ing its transactions can be undone instead of leaving
+//! the trie in a half-applied state.
+//!
+//! `Tx::apply_journaled` is `Tx::apply`'s journaled counterpart: each
+//! concrete transaction type records the prior value (or "absent") of
+//! every key it overwrites or inserts into a `Journal`, ahead of actually
+//! writing to it. `apply_block` drives a whole batch, keeping every
+//! transaction's `Journal` around, and replays them all in reverse the
+//! moment any transaction fails validation or application, restoring the
+//! trie to exactly its pre-block state.
+//!
+//! That guarantee only holds if a transaction that fails *partway through*
+//! `apply_journaled` hands back whatever it mutated before the error, same
+//! as one that succeeds outright - `apply_journaled` returns
+//! `Err((partial_journal, reason))` rather than a bare reason, and
+//! `apply_block` rolls the failing transaction's own partial journal back
+//! first, before unwinding the transactions that came before it.
+
+use crate::Tx;
+use patricia_trie::{TrieDB, TrieDBMut, TrieMut};
+use persistence::{Codec, DbHasher};
+
+/// One recorded mutation: the prior value held at `key`, or `None` if the
+/// key was absent before the write that's being journaled.
+struct JournalEntry {
+    key: Vec<u8>,
+    previous: Option<Vec<u8>>,
+}
+
+/// The set of trie mutations a single `Tx::apply_journaled` call made,
+/// in the order they happened.
+#[derive(Default)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    pub fn new() -> Journal {
+        Journal { entries: Vec::new() }
+    }
+
+    /// Records `key`'s value immediately before a write that's about to
+    /// happen - `None` if `key` was absent - so `rollback` can restore it
+    /// exactly. Called by each concrete transaction type's
+    /// `apply_journaled` ahead of every `trie.insert`/`trie.remove`.
+    pub fn record(&mut self, key: Vec<u8>, previous: Option<Vec<u8>>) {
+        self.entries.push(JournalEntry { key, previous });
+    }
+
+    /// Undoes every recorded mutation, most recent first, restoring the
+    /// keys this journal touched to their pre-apply values.
+    pub fn rollback(&self, trie: &mut TrieDBMut<DbHasher, Codec>) {
+        for entry in self.entries.iter().rev() {
+            match &entry.previous {
+                Some(value) => {
+                    let _ = trie.insert(&entry.key, value);
+                }
+                None => {
+                    let _ = trie.remove(&entry.key);
+                }
+            }
+        }
+    }
+}
+
+/// Why `apply_block` stopped applying transactions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApplyError {
+    /// `validate(trie)` rejected the transaction before it was applied.
+    Invalid,
+
+    /// The transaction's own `apply_journaled` failed partway through.
+    Failed,
+}
+
+/// Validates and applies `txs` against `trie_mut` in order. On the first
+/// failure, rolls back whatever partial mutations the failing transaction
+/// itself made, then every transaction applied before it, in reverse
+/// order, and returns the index and reason of the failing transaction,
+/// leaving `trie_mut` exactly as it was before this call - block
+/// application is all-or-nothing. Each transaction is validated against a
+/// trie view re-opened at `trie_mut`'s current root, so `txs[i]`'s
+/// `validate` sees every mutation `txs[0..i]` already committed.
+pub fn apply_block(
+    txs: &[Tx],
+    trie_mut: &mut TrieDBMut<DbHasher, Codec>,
+) -> Result<(), (usize, ApplyError)> {
+    let mut committed: Vec<Journal> = Vec::with_capacity(txs.len());
+
+    for (i, tx) in txs.iter().enumerate() {
+        let current_root = *trie_mut.root();
+        let valid = {
+            let view = TrieDB::new(trie_mut.db(), &current_root)
+                .expect("trie_mut's current root is always valid");
+            tx.validate(&view)
+        };
+        if !valid {
+            for journal in committed.iter().rev() {
+                journal.rollback(trie_mut);
+            }
+            return Err((i, ApplyError::Invalid));
+        }
+
+        match tx.apply_journaled(trie_mut) {
+            Ok(journal) => committed.push(journal),
+            Err((partial, err)) => {
+                partial.rollback(trie_mut);
+                for journal in committed.iter().rev() {
+                    journal.rollback(trie_mut);
+                }
+                return Err((i, err));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -33,6 +33,7 @@ extern crate bin_tools;
 #[macro_use]
 mod macros;
 
+mod block_builder;
 mod burn;
 mod call;
 mod change_minter;
@@ -41,10 +42,15 @@ mod create_mintable;
 mod create_unique;
 mod genesis;
 mod helpers;
+mod journal;
+mod mempool;
 mod mint;
 mod open_contract;
 mod send;
+mod tx_envelope;
+mod tx_proof;
 
+pub use crate::block_builder::*;
 pub use crate::burn::*;
 pub use crate::call::*;
 pub use crate::change_minter::*;
@@ -53,9 +59,13 @@ pub use crate::create_mintable::*;
 pub use crate::create_unique::*;
 pub use crate::genesis::*;
 pub use crate::helpers::*;
+pub use crate::journal::*;
+pub use crate::mempool::*;
 pub use crate::mint::*;
 pub use crate::open_contract::*;
 pub use crate::send::*;
+pub use crate::tx_envelope::*;
+pub use crate::tx_proof::*;
 
 use account::{Address, Balance, NormalAddress};
 use crypto::{FromBase58, Hash, Identity, PublicKey, SecretKey, ShortHash};
@@ -106,6 +116,30 @@ impl Tx {
         }
     }
 
+    /// `apply`'s journaled counterpart: records the prior value of every
+    /// key this transaction overwrites or inserts into a `Journal` ahead of
+    /// each write, so the mutations can be undone exactly. On success,
+    /// returns the complete `Journal`. On failure, still returns whatever
+    /// partial `Journal` was recorded before the error - a transaction that
+    /// fails partway through must never leave mutations only it knows how
+    /// to undo, or `apply_block` couldn't restore a clean pre-block trie.
+    pub fn apply_journaled(
+        &self,
+        trie: &mut TrieDBMut<DbHasher, Codec>,
+    ) -> Result<Journal, (Journal, ApplyError)> {
+        match *self {
+            Tx::Call(ref tx, _) => tx.apply_journaled(trie),
+            Tx::OpenContract(ref tx, _) => tx.apply_journaled(trie),
+            Tx::Send(ref tx, _) => tx.apply_journaled(trie),
+            Tx::Burn(ref tx, _) => tx.apply_journaled(trie),
+            Tx::CreateCurrency(ref tx, _) => tx.apply_journaled(trie),
+            Tx::CreateMintable(ref tx, _) => tx.apply_journaled(trie),
+            Tx::Mint(ref tx, _) => tx.apply_journaled(trie),
+            Tx::CreateUnique(ref tx, _) => tx.apply_journaled(trie),
+            Tx::ChangeMinter(ref tx, _) => tx.apply_journaled(trie),
+        }
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         match *self {
             Tx::Call(ref tx, _) => tx.to_bytes().unwrap(),
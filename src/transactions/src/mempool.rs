@@ -0,0 +1,383 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! A priority mempool for pending `Tx`s, ordered for block inclusion.
+//!
+//! Purple addresses are one-time-use: a transaction's `next_address()`
+//! becomes the only valid `creator_signing_address()` for whatever
+//! transaction comes next from that same chain. This means a transaction
+//! is only safe to include once its chain predecessor has actually landed
+//! (either already on-trie, or itself accepted into this pool), so every
+//! incoming transaction is partitioned into a `ready` set (whose link is
+//! satisfied) or a `future` set (waiting on that link to appear). Once a
+//! transaction is promoted into `ready`, its own `next_address()` is
+//! immediately registered as a satisfied link, which may cascade and
+//! promote whatever was waiting in `future` on it, and so on.
+//!
+//! Within `ready`, transactions are kept in a single global priority order
+//! by fee-per-byte (`nonce()` breaking ties), used both for block-building
+//! iteration via `ready_iter()` and for deciding which transactions to
+//! evict first when the pool is over `max_bytes`.
+
+use crate::Tx;
+use account::{Balance, NormalAddress};
+use crypto::Hash;
+use hashbrown::{HashMap, HashSet};
+use std::cmp::Ordering;
+
+/// What happened to a transaction accepted by `Mempool::insert`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InsertOutcome {
+    /// Accepted directly into the ready set.
+    Ready { evicted: Vec<Hash> },
+
+    /// Accepted into the future set, pending its preceding chain link.
+    Future { evicted: Vec<Hash> },
+
+    /// Replaced an existing transaction that shared the same
+    /// `creator_signing_address()` and didn't pay enough to survive.
+    Replaced { old: Hash, evicted: Vec<Hash> },
+}
+
+/// Why `Mempool::insert` refused a transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RejectReason {
+    /// The transaction has no computed hash to key it by.
+    MissingHash,
+
+    /// A transaction with this hash is already pending.
+    Duplicate,
+
+    /// A transaction already exists for this `creator_signing_address()`,
+    /// and the new one's fee doesn't clear the minimum replace-by-fee
+    /// bump over the old one.
+    FeeTooLowToReplace,
+
+    /// The pool is at `max_bytes` even after evicting every ready
+    /// transaction with a lower fee-per-byte than the incoming one.
+    PoolFull,
+}
+
+/// A `ready` entry, carrying the fee-per-byte inputs alongside the
+/// transaction so `priority_order` doesn't need to re-derive them (and
+/// re-clone the fee `Balance`) on every comparison.
+struct ReadyEntry {
+    tx: Tx,
+    fee_numerator: u64,
+    byte_size: usize,
+    nonce: u64,
+}
+
+pub struct Mempool {
+    /// Ready transactions, keyed by hash.
+    ready: HashMap<Hash, ReadyEntry>,
+
+    /// `ready`'s keys, sorted ascending by fee-per-byte (lowest first, so
+    /// eviction pops from the front and `ready_iter()` walks it in
+    /// reverse).
+    priority_order: Vec<Hash>,
+
+    /// Transactions waiting on a preceding chain link, keyed by the
+    /// `creator_signing_address()` they need to see become ready. At most
+    /// one transaction can wait per address, since that address can only
+    /// ever be spent once.
+    future: HashMap<NormalAddress, Tx>,
+
+    /// `creator_signing_address()` -> hash, covering both `ready` and
+    /// `future`, for replace-by-fee lookups.
+    by_sender: HashMap<NormalAddress, Hash>,
+
+    /// Addresses currently valid as a `creator_signing_address()`: either
+    /// seeded from on-trie state via `mark_address_ready`, or produced as
+    /// the `next_address()` of a transaction already in `ready`.
+    expected_addresses: HashSet<NormalAddress>,
+
+    total_bytes: usize,
+    max_bytes: usize,
+
+    /// Minimum percentage bump over the old fee that a replacement's fee
+    /// must exceed, e.g. `10` requires a fee strictly above the old fee
+    /// plus 10%.
+    min_replace_bump_percent: u64,
+}
+
+impl Mempool {
+    pub fn new(max_bytes: usize, min_replace_bump_percent: u64) -> Mempool {
+        Mempool {
+            ready: HashMap::new(),
+            priority_order: Vec::new(),
+            future: HashMap::new(),
+            by_sender: HashMap::new(),
+            expected_addresses: HashSet::new(),
+            total_bytes: 0,
+            max_bytes,
+            min_replace_bump_percent,
+        }
+    }
+
+    /// Registers `address` as a valid chain head, e.g. the current
+    /// on-trie signing address for an account the pool has no pending
+    /// transactions for yet. Promotes a matching `future` transaction
+    /// immediately, instead of it waiting for a link that already exists
+    /// on-chain but that this pool just hadn't been told about.
+    pub fn mark_address_ready(&mut self, address: NormalAddress) {
+        if self.expected_addresses.insert(address.clone()) {
+            if let Some(tx) = self.future.remove(&address) {
+                let hash = tx.tx_hash().expect("mempool only holds hashed txs");
+                self.insert_ready(hash, tx.clone());
+                self.promote_chain(&tx);
+            }
+        }
+    }
+
+    pub fn insert(&mut self, tx: Tx) -> Result<InsertOutcome, RejectReason> {
+        let hash = tx.tx_hash().ok_or(RejectReason::MissingHash)?;
+
+        if self.ready.contains_key(&hash) || self.future.values().any(|t| t.tx_hash() == Some(hash)) {
+            return Err(RejectReason::Duplicate);
+        }
+
+        let sender = tx.creator_signing_address();
+        let fee_numerator = fee_as_u64(&tx.fee());
+        let byte_size = tx.byte_size();
+
+        let mut replaced = None;
+        if let Some(&old_hash) = self.by_sender.get(&sender) {
+            let old_fee_numerator = self.fee_numerator_of(&old_hash);
+            let min_required =
+                old_fee_numerator + (old_fee_numerator * self.min_replace_bump_percent) / 100;
+            if fee_numerator <= min_required {
+                return Err(RejectReason::FeeTooLowToReplace);
+            }
+            self.remove_hash(&old_hash, &sender);
+            replaced = Some(old_hash);
+        }
+
+        let mut evicted = Vec::new();
+        if self.total_bytes + byte_size > self.max_bytes {
+            evicted = self.evict_for_space(byte_size, fee_numerator);
+            if evicted.is_empty() && self.total_bytes + byte_size > self.max_bytes {
+                return Err(RejectReason::PoolFull);
+            }
+        }
+
+        self.by_sender.insert(sender.clone(), hash);
+        self.total_bytes += byte_size;
+
+        let ready_now = self.expected_addresses.contains(&sender);
+        if ready_now {
+            self.insert_ready(hash, tx.clone());
+            self.promote_chain(&tx);
+        } else {
+            self.future.insert(sender, tx);
+        }
+
+        Ok(match (replaced, ready_now) {
+            (Some(old), _) => InsertOutcome::Replaced { old, evicted },
+            (None, true) => InsertOutcome::Ready { evicted },
+            (None, false) => InsertOutcome::Future { evicted },
+        })
+    }
+
+    /// Yields ready transactions in fee-per-byte priority order, highest
+    /// first. The order is by priority alone: a ready transaction whose
+    /// chain predecessor is also in `ready` is not held back until that
+    /// predecessor has been yielded, so it can come before it.
+    pub fn ready_iter(&self) -> impl Iterator<Item = &Tx> {
+        self.priority_order
+            .iter()
+            .rev()
+            .map(move |hash| &self.ready[hash].tx)
+    }
+
+    /// Drops mined transactions from the pool and advances their chains,
+    /// promoting whatever was waiting on the newly-confirmed link.
+    pub fn remove_mined(&mut self, hashes: &[Hash]) {
+        for hash in hashes {
+            if let Some(entry) = self.ready.remove(hash) {
+                self.total_bytes -= entry.byte_size;
+                if let Some(pos) = self.priority_order.iter().position(|h| h == hash) {
+                    self.priority_order.remove(pos);
+                }
+                self.by_sender.remove(&entry.tx.creator_signing_address());
+                self.promote_chain(&entry.tx);
+            } else {
+                self.remove_future_by_hash(hash);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ready.len() + self.future.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    /// Registers `tx.next_address()` as a satisfied link, cascading into
+    /// `future` if something was already waiting on it.
+    fn promote_chain(&mut self, tx: &Tx) {
+        let next = tx.next_address();
+        if self.expected_addresses.insert(next.clone()) {
+            if let Some(waiting) = self.future.remove(&next) {
+                let hash = waiting
+                    .tx_hash()
+                    .expect("mempool only holds hashed txs");
+                self.insert_ready(hash, waiting.clone());
+                self.promote_chain(&waiting);
+            }
+        }
+    }
+
+    fn remove_future_by_hash(&mut self, hash: &Hash) {
+        let addr = self
+            .future
+            .iter()
+            .find(|(_, tx)| tx.tx_hash() == Some(*hash))
+            .map(|(addr, _)| addr.clone());
+
+        if let Some(addr) = addr {
+            if let Some(tx) = self.future.remove(&addr) {
+                self.total_bytes -= tx.byte_size();
+                self.by_sender.remove(&addr);
+                self.promote_chain(&tx);
+            }
+        }
+    }
+
+    /// Removes a single pending transaction, wherever it's sitting, ahead
+    /// of a replacement taking its place. Doesn't touch `by_sender`; the
+    /// caller immediately re-inserts the replacement's own entry there.
+    fn remove_hash(&mut self, hash: &Hash, sender: &NormalAddress) {
+        if let Some(entry) = self.ready.remove(hash) {
+            self.total_bytes -= entry.byte_size;
+            if let Some(pos) = self.priority_order.iter().position(|h| h == hash) {
+                self.priority_order.remove(pos);
+            }
+        } else if let Some(tx) = self.future.remove(sender) {
+            self.total_bytes -= tx.byte_size();
+        }
+    }
+
+    fn fee_numerator_of(&self, hash: &Hash) -> u64 {
+        if let Some(entry) = self.ready.get(hash) {
+            return entry.fee_numerator;
+        }
+        for tx in self.future.values() {
+            if tx.tx_hash() == Some(*hash) {
+                return fee_as_u64(&tx.fee());
+            }
+        }
+        0
+    }
+
+    fn insert_ready(&mut self, hash: Hash, tx: Tx) {
+        let fee_numerator = fee_as_u64(&tx.fee());
+        let byte_size = tx.byte_size();
+        let nonce = tx.nonce();
+
+        let mut idx = self.priority_order.len();
+        for (i, existing) in self.priority_order.iter().enumerate() {
+            let entry = &self.ready[existing];
+            let ord = cmp_fee_rate(fee_numerator, byte_size, entry.fee_numerator, entry.byte_size)
+                .then_with(|| nonce.cmp(&entry.nonce));
+            if ord == Ordering::Less {
+                idx = i;
+                break;
+            }
+        }
+        self.priority_order.insert(idx, hash);
+        self.ready.insert(
+            hash,
+            ReadyEntry {
+                tx,
+                fee_numerator,
+                byte_size,
+                nonce,
+            },
+        );
+    }
+
+    /// Evicts the lowest fee-per-byte ready transactions until `needed`
+    /// bytes have been freed for an incoming transaction, but only those
+    /// whose rate is strictly lower than the incoming one's - never makes
+    /// room for a transaction by evicting something of equal or higher
+    /// priority. Returns the evicted hashes, or an empty `Vec` if not
+    /// enough space could be freed (in which case nothing is evicted).
+    fn evict_for_space(&mut self, incoming_bytes: usize, incoming_fee_numerator: u64) -> Vec<Hash> {
+        let needed = (self.total_bytes + incoming_bytes).saturating_sub(self.max_bytes);
+        if needed == 0 {
+            return Vec::new();
+        }
+
+        let mut freed = 0usize;
+        let mut victims = Vec::new();
+        for &hash in self.priority_order.iter() {
+            if freed >= needed {
+                break;
+            }
+            let entry = &self.ready[&hash];
+            if cmp_fee_rate(entry.fee_numerator, entry.byte_size, incoming_fee_numerator, incoming_bytes)
+                != Ordering::Less
+            {
+                // `priority_order` is ascending, so nothing from here on
+                // is a lower rate than the incoming transaction.
+                break;
+            }
+            freed += entry.byte_size;
+            victims.push(hash);
+        }
+
+        if freed < needed {
+            return Vec::new();
+        }
+
+        for hash in &victims {
+            if let Some(entry) = self.ready.remove(hash) {
+                self.total_bytes -= entry.byte_size;
+                self.by_sender.remove(&entry.tx.creator_signing_address());
+            }
+            if let Some(pos) = self.priority_order.iter().position(|h| h == hash) {
+                self.priority_order.remove(pos);
+            }
+        }
+        victims
+    }
+}
+
+/// Projects a fee `Balance` down to a `u64` for fee-per-byte comparisons.
+/// `Balance` has no `Div` impl, so ratios are never actually computed;
+/// `cmp_fee_rate` cross-multiplies instead. Also used by `block_builder`,
+/// which orders chain prefixes by the same rate.
+pub(crate) fn fee_as_u64(fee: &Balance) -> u64 {
+    fee.as_u64()
+}
+
+/// Compares two fee-per-byte rates, `fee_a / size_a` vs `fee_b / size_b`,
+/// via cross-multiplication so neither `Balance` nor this function needs
+/// to perform division or floating point arithmetic.
+pub(crate) fn cmp_fee_rate(fee_a: u64, size_a: usize, fee_b: u64, size_b: usize) -> Ordering {
+    let lhs = u128::from(fee_a) * (size_b.max(1) as u128);
+    let rhs = u128::from(fee_b) * (size_a.max(1) as u128);
+    lhs.cmp(&rhs)
+}
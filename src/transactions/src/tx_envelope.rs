@@ -0,0 +1,144 @@
+/*
+  Copyright (C) 2018-2020 The Purple Core Developers.
+  This file is part of the Purple Core Library.
+
+  The Purple Core Library is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  The Purple Core Library is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with the Purple Core Library. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! A self-describing wire frame around `Tx::to_bytes`/`Tx::from_bytes`,
+//! for relaying transactions between peers.
+//!
+//! `TxEnvelope` prefixes the raw transaction payload with a 4-byte network
+//! magic (`decode` accepts both `MAGIC_MAINNET` and `MAGIC_TESTNET`,
+//! reporting which in the returned `magic`, and rejects any other value)
+//! and a 1-byte protocol version, and trails it with a
+//! truncated `DbHasher` checksum over `(magic || version || payload)`.
+//! `decode` checks all three ahead of ever calling `Tx::from_bytes`, so a
+//! malformed or foreign-network frame is rejected with a specific error
+//! instead of an ambiguous parse failure somewhere inside the payload.
+
+use crate::Tx;
+use persistence::DbHasher;
+
+pub const MAGIC_MAINNET: [u8; 4] = *b"PURP";
+pub const MAGIC_TESTNET: [u8; 4] = *b"PURT";
+
+/// The only protocol version `TxEnvelope::decode` currently accepts.
+/// Bumped whenever the frame layout itself changes; new `Tx::TX_TYPE`
+/// values don't need a bump, since the payload is still just whatever
+/// `Tx::to_bytes`/`Tx::from_bytes` already agree on.
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// Length, in bytes, of the trailing checksum.
+const CHECKSUM_LEN: usize = 4;
+
+/// Magic(4) + version(1) + checksum(`CHECKSUM_LEN`), i.e. everything in
+/// the frame besides the transaction payload itself.
+const FRAME_OVERHEAD: usize = 4 + 1 + CHECKSUM_LEN;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxEnvelope {
+    pub magic: [u8; 4],
+    pub version: u8,
+    pub tx: Tx,
+}
+
+/// Why `TxEnvelope::decode` rejected a frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TxEnvelopeErr {
+    /// Too short to even contain the frame's fixed-size fields.
+    Truncated,
+
+    /// `magic` isn't a network this node recognizes.
+    WrongMagic,
+
+    /// `version` isn't `PROTOCOL_VERSION`.
+    UnsupportedVersion,
+
+    /// The trailing checksum doesn't match `(magic || version || payload)`.
+    BadChecksum,
+
+    /// The checksum passed, but `Tx::from_bytes` rejected the payload.
+    MalformedTx,
+}
+
+impl TxEnvelope {
+    pub fn new(magic: [u8; 4], tx: Tx) -> TxEnvelope {
+        TxEnvelope {
+            magic,
+            version: PROTOCOL_VERSION,
+            tx,
+        }
+    }
+
+    /// Produces `magic || version || payload || checksum`.
+    pub fn encode(&self) -> Vec<u8> {
+        let payload = self.tx.to_bytes();
+        let checksum = Self::checksum(&self.magic, self.version, &payload);
+
+        let mut buf = Vec::with_capacity(FRAME_OVERHEAD + payload.len());
+        buf.extend_from_slice(&self.magic);
+        buf.push(self.version);
+        buf.extend_from_slice(&payload);
+        buf.extend_from_slice(&checksum);
+        buf
+    }
+
+    /// Validates `magic`, `version` and the checksum, in that order,
+    /// before ever attempting `Tx::from_bytes` on the payload.
+    pub fn decode(bin: &[u8]) -> Result<TxEnvelope, TxEnvelopeErr> {
+        if bin.len() < FRAME_OVERHEAD {
+            return Err(TxEnvelopeErr::Truncated);
+        }
+
+        let mut magic = [0u8; 4];
+        magic.copy_from_slice(&bin[0..4]);
+        if magic != MAGIC_MAINNET && magic != MAGIC_TESTNET {
+            return Err(TxEnvelopeErr::WrongMagic);
+        }
+
+        let version = bin[4];
+        if version != PROTOCOL_VERSION {
+            return Err(TxEnvelopeErr::UnsupportedVersion);
+        }
+
+        let checksum_start = bin.len() - CHECKSUM_LEN;
+        let payload = &bin[5..checksum_start];
+        let given_checksum = &bin[checksum_start..];
+
+        let expected_checksum = Self::checksum(&magic, version, payload);
+        if given_checksum != expected_checksum.as_slice() {
+            return Err(TxEnvelopeErr::BadChecksum);
+        }
+
+        let tx = Tx::from_bytes(payload).map_err(|_| TxEnvelopeErr::MalformedTx)?;
+
+        Ok(TxEnvelope { magic, version, tx })
+    }
+
+    /// Truncated `DbHasher` digest over `(magic || version || payload)`.
+    fn checksum(magic: &[u8; 4], version: u8, payload: &[u8]) -> [u8; CHECKSUM_LEN] {
+        let mut preimage = Vec::with_capacity(4 + 1 + payload.len());
+        preimage.extend_from_slice(magic);
+        preimage.push(version);
+        preimage.extend_from_slice(payload);
+
+        let digest = DbHasher::hash(&preimage);
+        let digest_bytes = digest.as_ref();
+
+        let mut out = [0u8; CHECKSUM_LEN];
+        out.copy_from_slice(&digest_bytes[..CHECKSUM_LEN]);
+        out
+    }
+}